@@ -0,0 +1,180 @@
+// gameplay numbers that can be tuned without recompiling
+#[derive(Clone)]
+pub struct Config {
+	pub tickrate: f32, // updates per second
+	pub gravity: f32,
+	pub jump: f32,
+	pub move_speed: f32,
+	pub base_spell_speed: f32,
+	pub base_spell_range: f32,
+	pub ceiling_bounce: f32,
+	pub player_height: f32,
+	pub ground_check: f32, // how far past the player's feet and head to look for ground
+	pub max_modifiers: usize,
+	pub max_health: f32,
+	pub max_mana: f32,
+	pub mana_regen: f32, // per second
+}
+
+impl Default for Config {
+	fn default() -> Config {
+		Config {
+			tickrate: 100.0,
+			gravity: 10.0,
+			jump: 3.0,
+			move_speed: 1.0,
+			base_spell_speed: 5.0,
+			base_spell_range: 2.0,
+			ceiling_bounce: -0.01,
+			player_height: 0.2,
+			ground_check: 0.0001,
+			max_modifiers: 4,
+			max_health: 10.0,
+			max_mana: 10.0,
+			mana_regen: 1.0,
+		}
+	}
+}
+
+impl Config {
+	// sets a setting by name, refusing values that would break the game
+	pub fn set(&mut self, name: &str, value: f32) -> Result<(), String> {
+		if !value.is_finite() {
+			return Err(format!("`{}` has to be a finite number", name));
+		}
+		let setting = match name {
+			"max_modifiers" if value < 0.0 || value.fract() != 0.0 => {
+				return Err(format!("`{}` has to be a whole number, 0 or more", name))
+			}
+			"max_modifiers" => {
+				self.max_modifiers = value as usize;
+				return Ok(());
+			}
+			// the tick length is 1 / tickrate, and a player with no height can't stand
+			"tickrate" | "player_height" if value <= 0.0 => {
+				return Err(format!("`{}` has to be more than 0", name))
+			}
+			"tickrate" => &mut self.tickrate,
+			"gravity" => &mut self.gravity,
+			"jump" => &mut self.jump,
+			"move_speed" => &mut self.move_speed,
+			"base_spell_speed" => &mut self.base_spell_speed,
+			"base_spell_range" => &mut self.base_spell_range,
+			"ceiling_bounce" => &mut self.ceiling_bounce,
+			"player_height" => &mut self.player_height,
+			"ground_check" => &mut self.ground_check,
+			"max_health" => &mut self.max_health,
+			"max_mana" => &mut self.max_mana,
+			"mana_regen" => &mut self.mana_regen,
+			_ => return Err(format!("unknown setting `{}`", name)),
+		};
+		*setting = value;
+		Ok(())
+	}
+
+	// each line is a setting and its value, like `gravity 10`;
+	// settings that aren't mentioned keep their defaults
+	pub fn load(path: &str) -> Result<Config, String> {
+		let file = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+		let mut config = Config::default();
+		for (i, line) in file.lines().enumerate() {
+			let error = |message: String| format!("{}:{}: {}", path, i + 1, message);
+			let words: Vec<&str> = line.split('#').next().unwrap().split_whitespace().collect();
+			match words.as_slice() {
+				[] => {}
+				[name, value] => {
+					let value =
+						value.parse().map_err(|_| error(format!("bad number `{}`", value)))?;
+					config.set(name, value).map_err(error)?;
+				}
+				_ => return Err(error("expected a setting and a value".to_string())),
+			}
+		}
+		Ok(config)
+	}
+
+	// loads the config file, then applies `--setting value` or `--setting=value` flags from
+	// the command line on top so that they outlast edits to the file
+	pub fn load_with_args(path: &str, args: &[String]) -> Result<Config, String> {
+		let mut config = Config::load(path)?;
+		for (name, value) in flags(args)? {
+			if PATH_FLAGS.contains(&name) {
+				continue;
+			}
+			let value =
+				value.parse().map_err(|_| format!("--{}: bad number `{}`", name, value))?;
+			config.set(name, value).map_err(|e| format!("--{}: {}", name, e))?;
+		}
+		Ok(config)
+	}
+}
+
+// command line flags that name files rather than settings
+const PATH_FLAGS: [&str; 2] = ["config", "script"];
+
+// the names and values of `--name value` and `--name=value` flags
+fn flags(args: &[String]) -> Result<Vec<(&str, &str)>, String> {
+	let mut flags = vec![];
+	let mut args = args.iter();
+	while let Some(arg) = args.next() {
+		let flag = arg
+			.strip_prefix("--")
+			.ok_or_else(|| format!("expected a `--setting` flag, not `{}`", arg))?;
+		let (name, value) = match flag.split_once('=') {
+			Some(pair) => pair,
+			None => {
+				(flag, args.next().ok_or_else(|| format!("`{}` needs a value", arg))?.as_str())
+			}
+		};
+		flags.push((name, value));
+	}
+	Ok(flags)
+}
+
+// the path given by a `--name path` or `--name=path` flag on the command line
+pub fn path<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+	flags(args).ok()?.into_iter().find(|(flag, _)| *flag == name).map(|(_, path)| path)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn set_rejects_values_that_break_the_game() {
+		let mut config = Config::default();
+		assert!(config.set("tickrate", 0.0).is_err());
+		assert!(config.set("player_height", -0.1).is_err());
+		assert!(config.set("max_modifiers", -1.0).is_err());
+		assert!(config.set("max_modifiers", 1.5).is_err());
+		assert!(config.set("gravity", f32::NAN).is_err());
+		assert_eq!(config.set("speed", 1.0), Err("unknown setting `speed`".to_string()));
+		assert_eq!((config.tickrate, config.max_modifiers), (100.0, 4));
+		assert_eq!(config.set("tickrate", 60.0), Ok(()));
+		assert_eq!(config.tickrate, 60.0);
+	}
+
+	#[test]
+	fn args_go_through_set() {
+		let path = std::env::temp_dir().join(format!("feto-config-{}.txt", std::process::id()));
+		let path = path.to_str().unwrap();
+		std::fs::write(path, "gravity 5\njump 1\n").unwrap();
+		let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+		let flags =
+			args(&["--script", "demo.txt", "--jump=4", "--tickrate", "60", "--config=c"]);
+		let config = Config::load_with_args(path, &flags);
+		let errors = [
+			Config::load_with_args(path, &args(&["--tickrate=0"])).err(),
+			Config::load_with_args(path, &args(&["--jump=high"])).err(),
+			Config::load_with_args(path, &args(&["--jump"])).err(),
+		];
+		std::fs::remove_file(path).unwrap();
+		let config = config.unwrap();
+		assert_eq!((config.gravity, config.jump, config.tickrate), (5.0, 4.0, 60.0));
+		assert_eq!(super::path(&flags, "config"), Some("c"));
+		assert_eq!(super::path(&flags, "script"), Some("demo.txt"));
+		assert_eq!(errors[0].as_deref(), Some("--tickrate: `tickrate` has to be more than 0"));
+		assert_eq!(errors[1].as_deref(), Some("--jump: bad number `high`"));
+		assert_eq!(errors[2].as_deref(), Some("`--jump` needs a value"));
+	}
+}
@@ -0,0 +1,202 @@
+use crate::hud::{self, Line};
+use crate::input::Button;
+use crate::spells::Element;
+use crate::vector::Vector;
+use crate::{World, TIME_SCALES};
+use winit::event::{ElementState, VirtualKeyCode};
+
+const LOG_LINES: usize = 8; // shown above the prompt
+const MAX_STEPS: usize = 60_000; // ten minutes at the default tickrate
+
+pub struct Command {
+	pub name: &'static str,
+	pub usage: &'static str,
+	run: fn(&mut World, &[&str]) -> Result<String, String>,
+}
+
+pub const COMMANDS: [Command; 9] = [
+	Command { name: "help", usage: "help", run: help },
+	Command { name: "spawn", usage: "spawn x y [w h]  adds a target", run: spawn },
+	Command { name: "teleport", usage: "teleport x y", run: teleport },
+	Command { name: "set", usage: "set setting value", run: set },
+	Command { name: "mana", usage: "mana [amount]  fills up by default", run: mana },
+	Command { name: "cast", usage: "cast element...  towards the mouse", run: cast },
+	Command { name: "step", usage: "step [ticks]", run: step },
+	Command { name: "pause", usage: "pause  toggles", run: pause },
+	Command { name: "speed", usage: "speed scale  from 0.1 to 4", run: speed },
+];
+
+fn number(args: &[&str], i: usize) -> Result<f32, String> {
+	let arg = args.get(i).ok_or("not enough arguments")?;
+	arg.parse().map_err(|_| format!("bad number `{}`", arg))
+}
+
+fn help(_: &mut World, _: &[&str]) -> Result<String, String> {
+	Ok(COMMANDS.iter().map(|command| command.usage).collect::<Vec<_>>().join("\n"))
+}
+
+fn spawn(world: &mut World, args: &[&str]) -> Result<String, String> {
+	let pos = Vector::new(number(args, 0)?, number(args, 1)?);
+	let size = match args.len() {
+		2 => Vector::new(0.1, 0.1),
+		_ => Vector::new(number(args, 2)?, number(args, 3)?),
+	};
+	Ok(format!("spawned target {}", world.add_target(pos, size)))
+}
+
+fn teleport(world: &mut World, args: &[&str]) -> Result<String, String> {
+	let player = world.objects.get_mut(&world.player_id).unwrap();
+	player.pos = Vector::new(number(args, 0)?, number(args, 1)?);
+	player.vel = Vector::new(0.0, 0.0);
+	Ok(String::new())
+}
+
+fn set(world: &mut World, args: &[&str]) -> Result<String, String> {
+	let name = args.first().ok_or("not enough arguments")?;
+	let mut config = world.config.clone();
+	config.set(name, number(args, 1)?)?;
+	world.set_config(config);
+	Ok(String::new())
+}
+
+fn mana(world: &mut World, args: &[&str]) -> Result<String, String> {
+	let amount = match args.is_empty() {
+		true => world.config.max_mana,
+		false => number(args, 0)?,
+	};
+	world.mana = (world.mana + amount).min(world.config.max_mana);
+	Ok(format!("mana {:.1}", world.mana))
+}
+
+fn cast(world: &mut World, args: &[&str]) -> Result<String, String> {
+	let elements = args
+		.iter()
+		.map(|name| {
+			Element::from_name(name).ok_or_else(|| format!("unknown element `{}`", name))
+		})
+		.collect::<Result<Vec<Element>, String>>()?;
+	// what the player had queued is left alone if the cast fails
+	let queued = std::mem::replace(&mut world.elements, elements);
+	let id = match world.cast() {
+		Ok(id) => id,
+		Err(e) => {
+			world.elements = queued;
+			return Err(e);
+		}
+	};
+	let stats = world.spells.get(&id).unwrap();
+	Ok(format!("cast {} as {}", stats.name.as_deref().unwrap_or("a spell"), id))
+}
+
+fn step(world: &mut World, args: &[&str]) -> Result<String, String> {
+	let ticks = match args.is_empty() {
+		true => 1,
+		false => match number(args, 0)? {
+			ticks if (0.0..=MAX_STEPS as f32).contains(&ticks) => ticks as usize,
+			ticks => return Err(format!("can step 0 to {} ticks, not {}", MAX_STEPS, ticks)),
+		},
+	};
+	for _ in 0..ticks {
+		world.tick();
+	}
+	Ok(String::new())
+}
+
+fn pause(world: &mut World, _: &[&str]) -> Result<String, String> {
+	world.paused = !world.paused;
+	Ok(if world.paused { "paused" } else { "resumed" }.to_string())
+}
+
+fn speed(world: &mut World, args: &[&str]) -> Result<String, String> {
+	let (min, max) = (TIME_SCALES[0], TIME_SCALES[TIME_SCALES.len() - 1]);
+	world.time_scale = number(args, 0)?.clamp(min, max);
+	Ok(format!("speed {}x", world.time_scale))
+}
+
+// runs a line such as `cast fire air air`, returning what it has to say
+pub fn execute(world: &mut World, line: &str) -> Result<String, String> {
+	let words: Vec<&str> = line.split_whitespace().collect();
+	let (name, args) = match words.split_first() {
+		Some(split) => split,
+		None => return Ok(String::new()),
+	};
+	let command = COMMANDS
+		.iter()
+		.find(|command| command.name == *name)
+		.ok_or_else(|| format!("unknown command `{}`, try `help`", name))?;
+	(command.run)(world, args)
+}
+
+// runs each line of a file as a command, stopping at the first that fails
+pub fn script(world: &mut World, path: &str) -> Result<(), String> {
+	let file = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+	for (i, line) in file.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		println!("> {}", line);
+		let output = execute(world, line).map_err(|e| format!("{}:{}: {}", path, i + 1, e))?;
+		if !output.is_empty() {
+			println!("{}", output);
+		}
+	}
+	Ok(())
+}
+
+// a line of commands typed in game, with what they printed above it
+#[derive(Default)]
+pub struct Console {
+	input: String,
+	log: Vec<Line>,
+	history: Vec<String>,
+	recalled: usize, // how far back up the history the input came from
+}
+
+impl Console {
+	pub fn type_char(&mut self, c: char) {
+		if !c.is_control() && c != '`' {
+			self.input.push(c);
+		}
+	}
+
+	// handles a key while the console is open, returning false once it should close
+	pub fn key(&mut self, world: &mut World, button: Button, state: ElementState) -> bool {
+		use ElementState::*;
+		match (state, button) {
+			(Released, Button::Key(VirtualKeyCode::Grave | VirtualKeyCode::Escape)) => {
+				return false
+			}
+			(Pressed, Button::Key(VirtualKeyCode::Back)) => {
+				self.input.pop();
+			}
+			(Pressed, Button::Key(VirtualKeyCode::Up)) if self.recalled < self.history.len() => {
+				self.recalled += 1;
+				self.input = self.history[self.history.len() - self.recalled].clone();
+			}
+			(Pressed, Button::Key(VirtualKeyCode::Return)) => {
+				let line = std::mem::take(&mut self.input);
+				self.log.push(hud::line(&format!("> {}", line), hud::GREY));
+				match execute(world, &line) {
+					Ok(output) => {
+						self.log.extend(output.lines().map(|text| hud::line(text, hud::WHITE)))
+					}
+					Err(e) => self.log.push(hud::line(&e, hud::RED)),
+				}
+				if !line.trim().is_empty() {
+					self.history.push(line);
+				}
+				self.recalled = 0;
+			}
+			_ => {}
+		}
+		true
+	}
+
+	pub fn lines(&self) -> Vec<Line> {
+		let start = self.log.len().saturating_sub(LOG_LINES);
+		let mut lines = self.log[start..].to_vec();
+		lines.push(hud::line(&format!("> {}_", self.input), [1.0, 1.0, 0.4]));
+		lines
+	}
+}
@@ -0,0 +1,156 @@
+use crate::editor::outline;
+use crate::hud::{self, Line};
+use crate::object::Shape;
+use crate::render::{Color, Textured, Vertex};
+use crate::sprite::Atlas;
+use crate::vector::Vector;
+use crate::{Id, World};
+
+const VELOCITY_SCALE: f32 = 0.1; // seconds of movement each velocity arrow shows
+const CONTACT_SIZE: f32 = 0.01;
+const LABEL_HEIGHT: f32 = 0.025;
+const SMOOTHING: f32 = 0.05; // of each new frame that goes into the averages
+const PLAYER: Color = [0.4, 1.0, 0.4];
+const GROUND: Color = [0.4, 0.6, 1.0];
+const OTHER: Color = [1.0, 1.0, 1.0];
+const VELOCITY: Color = [1.0, 1.0, 0.4];
+const CONTACT: Color = [1.0, 0.3, 0.3];
+const TOUCHING: Color = [0.3, 1.0, 1.0];
+
+// running averages of how fast frames and ticks are going
+#[derive(Default)]
+pub struct Stats {
+	frame_time: f32, // seconds
+	tick_rate: f32,  // ticks per second
+}
+
+impl Stats {
+	pub fn record(&mut self, delta_time: f32, ticks: f32) {
+		if delta_time <= 0.0 {
+			return;
+		}
+		self.frame_time += (delta_time - self.frame_time) * SMOOTHING;
+		self.tick_rate += (ticks / delta_time - self.tick_rate) * SMOOTHING;
+	}
+}
+
+// a thin quad from a to b
+fn segment(vertices: &mut Vec<Vertex<Color>>, a: Vector, b: Vector, color: Color) {
+	let side = match (b - a).normalized() {
+		Some(dir) => Vector::new(-dir.y, dir.x) * 0.003,
+		None => return,
+	};
+	vertices.extend([
+		Vertex::new((a - side).x, (a - side).y, color),
+		Vertex::new((b - side).x, (b - side).y, color),
+		Vertex::new((a + side).x, (a + side).y, color),
+		Vertex::new((b + side).x, (b + side).y, color),
+		Vertex::new((a + side).x, (a + side).y, color),
+		Vertex::new((b - side).x, (b - side).y, color),
+	]);
+}
+
+fn color(world: &World, id: Id) -> Color {
+	if id == world.player_id {
+		PLAYER
+	} else if world.ground_ids.contains(&id) {
+		GROUND
+	} else if let Some(stats) = world.spells.get(&id) {
+		stats.element.color()
+	} else if let Some(trigger) = world.triggers.get(&id) {
+		trigger.kind.color()
+	} else {
+		OTHER
+	}
+}
+
+// where each moving object touches the ground: the middle of the overlap between its
+// bounds, grown by the ground check distance, and each solid's
+fn contacts(world: &World) -> Vec<Vector> {
+	let mut points = vec![];
+	for (id, object) in &world.objects {
+		// stationary spells are solid themselves, so would touch their own bounds
+		let stationary = world.spells.get(id).is_some_and(|stats| stats.is_stationary());
+		if world.ground_ids.contains(id) || world.triggers.contains_key(id) || stationary {
+			continue;
+		}
+		let (min, max) = object.bounds();
+		let (min, max) = (min - world.config.ground_check, max + world.config.ground_check);
+		for solid in world.solids(object, 0.0) {
+			let (smin, smax) = solid.bounds();
+			let (lo, hi) = (
+				Vector::new(min.x.max(smin.x), min.y.max(smin.y)),
+				Vector::new(max.x.min(smax.x), max.y.min(smax.y)),
+			);
+			if lo.x <= hi.x && lo.y <= hi.y {
+				points.push((lo + hi) * 0.5);
+			}
+		}
+	}
+	points
+}
+
+// every object's outline and velocity, the player's ground probes and contact points
+pub fn overlay(world: &World) -> Vec<Vertex<Color>> {
+	let mut vertices = vec![];
+	let mut ids: Vec<&Id> = world.objects.keys().collect();
+	ids.sort();
+	for id in ids {
+		let object = world.objects.get(id).unwrap();
+		let color = color(world, *id);
+		match object.shape {
+			Shape::Aabb(_) => {
+				let (min, max) = object.bounds();
+				outline(&mut vertices, min, max, color);
+			}
+			Shape::Line(dir) => segment(&mut vertices, object.pos, object.pos + dir, color),
+		}
+		let end = object.pos + object.vel * VELOCITY_SCALE;
+		segment(&mut vertices, object.pos, end, VELOCITY);
+	}
+	for (probe, touching) in world.probes() {
+		if let Shape::Line(dir) = probe.shape {
+			let color = if touching { TOUCHING } else { hud::GREY };
+			segment(&mut vertices, probe.pos, probe.pos + dir, color);
+		}
+	}
+	for point in contacts(world) {
+		let (min, max) = (point - CONTACT_SIZE, point + CONTACT_SIZE);
+		vertices.extend([
+			Vertex::new(min.x, min.y, CONTACT),
+			Vertex::new(max.x, min.y, CONTACT),
+			Vertex::new(min.x, max.y, CONTACT),
+			Vertex::new(max.x, max.y, CONTACT),
+			Vertex::new(min.x, max.y, CONTACT),
+			Vertex::new(max.x, min.y, CONTACT),
+		]);
+	}
+	vertices
+}
+
+// each object's id, next to its top left corner
+pub fn labels(vertices: &mut Vec<Vertex<Textured>>, font: &Atlas, world: &World) {
+	for (id, object) in &world.objects {
+		let (min, max) = object.bounds();
+		let line = hud::line(&id.to_string(), color(world, *id));
+		hud::text(
+			vertices,
+			font,
+			&[line],
+			Vector::new(min.x, max.y + LABEL_HEIGHT),
+			LABEL_HEIGHT,
+		);
+	}
+}
+
+pub fn panel(world: &World, stats: &Stats) -> Vec<Line> {
+	vec![
+		hud::line("debug (f3 hides)", VELOCITY),
+		hud::line(
+			&format!("ticks {:.0} / s (target {:.0})", stats.tick_rate, world.config.tickrate),
+			hud::WHITE,
+		),
+		hud::line(&format!("objects {}", world.objects.len()), hud::WHITE),
+		hud::line(&format!("frame {:.1} ms", stats.frame_time * 1000.0), hud::WHITE),
+	]
+}
@@ -0,0 +1,219 @@
+use crate::hud::{self, Line};
+use crate::input::Button;
+use crate::materials::Material;
+use crate::object::{Object, Shape};
+use crate::render::{Color, Vertex};
+use crate::vector::Vector;
+use crate::{Id, World, MIN_BLOCK};
+use winit::event::{MouseButton, VirtualKeyCode};
+
+const GRID: f32 = 0.05;
+const HANDLE: f32 = 0.03; // how close to a corner a click has to be to resize from it
+const PALETTE: [Color; 6] = [
+	[1.0, 1.0, 1.0],
+	[0.6, 0.6, 0.6],
+	[0.8, 0.55, 0.3],
+	[0.5, 0.9, 0.4],
+	[0.7, 0.9, 1.0],
+	[0.9, 0.5, 0.5],
+];
+
+enum Drag {
+	Move(Vector),   // from the mouse to the block's min corner
+	Resize(Vector), // the corner that stays put
+}
+
+// places, drags, resizes and deletes ground blocks with the mouse while the game is paused
+pub struct Editor {
+	selected: Option<Id>,
+	drag: Option<Drag>,
+	snap: bool,
+}
+
+impl Default for Editor {
+	fn default() -> Editor {
+		Editor { selected: None, drag: None, snap: true }
+	}
+}
+
+fn snap(v: Vector) -> Vector {
+	Vector::new((v.x / GRID).round() * GRID, (v.y / GRID).round() * GRID)
+}
+
+// appends a thin outline of the box
+pub fn outline(vertices: &mut Vec<Vertex<Color>>, min: Vector, max: Vector, color: Color) {
+	let w = 0.005;
+	for (a, b) in [
+		(Vector::new(min.x - w, min.y - w), Vector::new(max.x + w, min.y + w)),
+		(Vector::new(min.x - w, max.y - w), Vector::new(max.x + w, max.y + w)),
+		(Vector::new(min.x - w, min.y - w), Vector::new(min.x + w, max.y + w)),
+		(Vector::new(max.x - w, min.y - w), Vector::new(max.x + w, max.y + w)),
+	] {
+		vertices.extend([
+			Vertex::new(a.x, a.y, color),
+			Vertex::new(b.x, a.y, color),
+			Vertex::new(a.x, b.y, color),
+			Vertex::new(b.x, b.y, color),
+			Vertex::new(a.x, b.y, color),
+			Vertex::new(b.x, a.y, color),
+		]);
+	}
+}
+
+impl Editor {
+	fn mouse(&self, world: &World) -> Vector {
+		if self.snap {
+			snap(world.mouse)
+		} else {
+			world.mouse
+		}
+	}
+
+	pub fn update(&mut self, world: &mut World) {
+		world.snapshot = world.input.sample();
+		// reloading the level replaces every block
+		if !self.selected.is_some_and(|id| world.ground_ids.contains(&id)) {
+			self.selected = None;
+			self.drag = None;
+		}
+		let pressed = |key| world.snapshot.presses(Button::Key(key)) > 0;
+		let (delete, material, color, spawn, grid) = (
+			pressed(VirtualKeyCode::Delete) || pressed(VirtualKeyCode::Back),
+			pressed(VirtualKeyCode::M),
+			pressed(VirtualKeyCode::C),
+			pressed(VirtualKeyCode::P),
+			pressed(VirtualKeyCode::G),
+		);
+		let save = pressed(VirtualKeyCode::S)
+			&& (world.snapshot.held(Button::Key(VirtualKeyCode::LControl))
+				|| world.snapshot.held(Button::Key(VirtualKeyCode::RControl)));
+		let left = Button::Mouse(MouseButton::Left);
+		let mouse = self.mouse(world);
+
+		if world.snapshot.presses(left) > 0 {
+			// the last block drawn is on top, so it's the one that gets picked
+			let under = world.ground_ids.iter().rev().copied().find(|id| {
+				let (min, max) = world.objects.get(id).unwrap().bounds();
+				crate::object::aabb_aabb(min, max, world.mouse, world.mouse)
+					|| (world.mouse - min).length() < HANDLE
+					|| (world.mouse - max).length() < HANDLE
+			});
+			self.drag = Some(match under {
+				Some(id) => {
+					self.selected = Some(id);
+					let (min, max) = world.objects.get(&id).unwrap().bounds();
+					if (world.mouse - max).length() < HANDLE {
+						Drag::Resize(min)
+					} else if (world.mouse - min).length() < HANDLE {
+						Drag::Resize(max)
+					} else {
+						Drag::Move(min - mouse)
+					}
+				}
+				None => {
+					let block =
+						Object::new(mouse.x, mouse.y, Shape::Aabb(Vector::new(GRID, GRID)));
+					self.selected = Some(world.add_ground(block, Material::Stone));
+					Drag::Resize(mouse)
+				}
+			});
+		}
+		if !world.snapshot.held(left) {
+			self.drag = None;
+		}
+		if let (Some(id), Some(drag)) = (self.selected, &self.drag) {
+			let (old_min, old_max) = world.objects.get(&id).unwrap().bounds();
+			let (min, max) = match drag {
+				Drag::Move(offset) => {
+					let min = if self.snap { snap(mouse + *offset) } else { mouse + *offset };
+					(min, min + (old_max - old_min))
+				}
+				Drag::Resize(anchor) => (
+					Vector::new(anchor.x.min(mouse.x), anchor.y.min(mouse.y)),
+					Vector::new(anchor.x.max(mouse.x), anchor.y.max(mouse.y)),
+				),
+			};
+			let size = max - min;
+			let size = Vector::new(size.x.max(MIN_BLOCK), size.y.max(MIN_BLOCK));
+			let center = min + size * 0.5;
+			world.move_ground(id, Object::new(center.x, center.y, Shape::Aabb(size)));
+		}
+
+		if let Some(id) = self.selected {
+			if delete {
+				world.remove_ground(id);
+				self.selected = None;
+				self.drag = None;
+			} else if material {
+				let current = Material::ALL.iter().position(|m| *m == world.material(id));
+				let next = Material::ALL[current.map_or(0, |i| i + 1) % Material::ALL.len()];
+				world.materials.insert(id, next);
+				world.colors.insert(id, next.color());
+				world.terrain_changed.insert(id);
+			} else if color {
+				let current = PALETTE.iter().position(|c| *c == *world.colors.get(&id).unwrap());
+				let next = PALETTE[current.map_or(0, |i| i + 1) % PALETTE.len()];
+				world.colors.insert(id, next);
+				world.terrain_changed.insert(id);
+			}
+		}
+		if spawn {
+			world.spawn = mouse;
+			let player = world.objects.get_mut(&world.player_id).unwrap();
+			player.pos = mouse;
+			player.vel = Vector::new(0.0, 0.0);
+		}
+		if grid {
+			self.snap = !self.snap;
+		}
+		if save {
+			match world.level().save(&world.level_path) {
+				Ok(()) => eprintln!("saved {}", world.level_path),
+				Err(e) => eprintln!("{}", e),
+			}
+		}
+	}
+
+	// outlines the selected block and marks the spawn
+	pub fn overlay(&self, world: &World) -> Vec<Vertex<Color>> {
+		let mut vertices = vec![];
+		if let Some(id) = self.selected {
+			let (min, max) = world.objects.get(&id).unwrap().bounds();
+			outline(&mut vertices, min, max, [1.0, 1.0, 0.4]);
+		}
+		let spawn = world.spawn;
+		outline(&mut vertices, spawn - 0.02, spawn + 0.02, [0.4, 1.0, 0.4]);
+		vertices
+	}
+
+	pub fn help(&self, world: &World) -> Vec<Line> {
+		let mut lines = vec![
+			hud::line("editing (f2 plays)", [1.0, 1.0, 0.4]),
+			hud::line("drag a block to move it, its corner to resize it,", hud::GREY),
+			hud::line("or empty space to draw a new one", hud::GREY),
+			hud::line(
+				&format!(
+					"delete  m material  c color  p spawn  g grid ({})  ctrl+s save",
+					if self.snap { "on" } else { "off" }
+				),
+				hud::GREY,
+			),
+		];
+		if let Some(id) = self.selected {
+			let (min, max) = world.objects.get(&id).unwrap().bounds();
+			let (pos, size) = ((min + max) * 0.5, max - min);
+			lines.push(hud::line(
+				&format!(
+					"{} {:.2} x {:.2} at ({:.2}, {:.2})",
+					world.material(id).name(),
+					size.x,
+					size.y,
+					pos.x,
+					pos.y
+				),
+				hud::WHITE,
+			));
+		}
+		lines
+	}
+}
@@ -0,0 +1,130 @@
+use crate::vector::Vector;
+use std::collections::{BTreeMap, HashMap};
+
+pub const CELL: f32 = 0.05; // width and height of a fluid cell
+const MIN_AMOUNT: f32 = 0.005; // cells with less than this dry up
+const FLOOR: i32 = -200; // cells that fall below this row are gone for good
+
+#[derive(Clone, Copy, Default)]
+pub struct Cell {
+	pub amount: f32, // 1 is full, more than that is pushed upwards
+	pub charge: f32, // seconds left of being electrified
+}
+
+// a grid of water cells that fall, spread out sideways and pool on anything solid
+#[derive(Default)]
+pub struct Fluid {
+	cells: BTreeMap<(i32, i32), Cell>, // keyed by (row, column) so iteration goes bottom up
+	ticks: u32,
+}
+
+pub fn cell_at(pos: Vector) -> (i32, i32) {
+	((pos.y / CELL).floor() as i32, (pos.x / CELL).floor() as i32)
+}
+
+pub fn bounds((y, x): (i32, i32)) -> (Vector, Vector) {
+	let min = Vector::new(x as f32 * CELL, y as f32 * CELL);
+	(min, min + CELL)
+}
+
+impl Fluid {
+	fn amount(&self, key: (i32, i32)) -> f32 {
+		self.cells.get(&key).map_or(0.0, |cell| cell.amount)
+	}
+
+	fn transfer(&mut self, from: (i32, i32), to: (i32, i32), amount: f32) {
+		self.cells.entry(from).or_default().amount -= amount;
+		self.cells.entry(to).or_default().amount += amount;
+	}
+
+	pub fn add(&mut self, pos: Vector, amount: f32) {
+		self.cells.entry(cell_at(pos)).or_default().amount += amount;
+	}
+
+	// takes up to the amount from the cell at pos, for example when it boils away
+	pub fn remove(&mut self, pos: Vector, amount: f32) {
+		if let Some(cell) = self.cells.get_mut(&cell_at(pos)) {
+			cell.amount = (cell.amount - amount).max(0.0);
+		}
+	}
+
+	pub fn depth(&self, pos: Vector) -> f32 {
+		self.amount(cell_at(pos)).min(1.0)
+	}
+
+	pub fn charge(&self, pos: Vector) -> f32 {
+		self.cells.get(&cell_at(pos)).map_or(0.0, |cell| cell.charge)
+	}
+
+	// charges every cell of the body of water connected to the cell at pos
+	pub fn electrify(&mut self, pos: Vector, time: f32) {
+		let mut stack = vec![cell_at(pos)];
+		while let Some((y, x)) = stack.pop() {
+			match self.cells.get_mut(&(y, x)) {
+				Some(cell) if cell.amount > MIN_AMOUNT && cell.charge < time => {
+					cell.charge = time
+				}
+				_ => continue,
+			}
+			stack.extend([(y - 1, x), (y + 1, x), (y, x - 1), (y, x + 1)]);
+		}
+	}
+
+	pub fn cells(&self) -> impl Iterator<Item = ((i32, i32), &Cell)> {
+		self.cells.iter().map(|(key, cell)| (*key, cell))
+	}
+
+	// moves water for one tick; solid says whether the box between min and max is blocked
+	pub fn step(&mut self, delta_time: f32, solid: impl Fn(Vector, Vector) -> bool) {
+		let mut blocked = HashMap::new();
+		let mut is_solid = |key: (i32, i32)| {
+			*blocked.entry(key).or_insert_with(|| {
+				let (min, max) = bounds(key);
+				solid(min, max)
+			})
+		};
+		// alternate which side goes first so water doesn't drift one way
+		self.ticks += 1;
+		let sides = if self.ticks % 2 == 1 { [1, -1] } else { [-1, 1] };
+
+		let keys: Vec<(i32, i32)> = self.cells.keys().copied().collect();
+		for key in keys {
+			let (y, x) = key;
+			let (up, down) = ((y + 1, x), (y - 1, x));
+			if y < FLOOR {
+				self.cells.remove(&key);
+				continue;
+			}
+			// water that ended up inside something solid squeezes out of the top
+			if is_solid(key) {
+				let amount = self.amount(key);
+				self.transfer(key, up, amount);
+				self.cells.remove(&key);
+				continue;
+			}
+
+			if !is_solid(down) {
+				let room = (1.0 - self.amount(down)).max(0.0);
+				self.transfer(key, down, self.amount(key).min(room));
+			}
+			for side in sides {
+				let side = (y, x + side);
+				if !is_solid(side) {
+					let difference = self.amount(key) - self.amount(side);
+					if difference > 0.0 {
+						self.transfer(key, side, difference / 3.0);
+					}
+				}
+			}
+			let excess = self.amount(key) - 1.0;
+			if excess > 0.0 && !is_solid(up) {
+				self.transfer(key, up, excess);
+			}
+		}
+
+		self.cells.retain(|_, cell| {
+			cell.charge = (cell.charge - delta_time).max(0.0);
+			cell.amount >= MIN_AMOUNT
+		});
+	}
+}
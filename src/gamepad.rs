@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PadButton {
+	South,
+	East,
+	North,
+	West,
+	LeftTrigger,
+	LeftTrigger2,
+	RightTrigger,
+	RightTrigger2,
+	Select,
+	Start,
+	LeftThumb,
+	RightThumb,
+	DPadUp,
+	DPadDown,
+	DPadLeft,
+	DPadRight,
+}
+
+// only constructed by the gilrs backend, or by tests through the fake one
+#[cfg_attr(not(any(feature = "gamepad", test)), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PadAxis {
+	LeftStickX,
+	LeftStickY,
+	RightStickX,
+	RightStickY,
+}
+
+#[cfg_attr(not(any(feature = "gamepad", test)), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+	Pressed(PadButton),
+	Released(PadButton),
+	Axis(PadAxis, f32), // in [-1, 1], with +y up
+}
+
+// where gamepad events come from; events from every connected gamepad are merged
+pub trait Backend {
+	fn poll(&mut self) -> Vec<Event>;
+}
+
+// replays queued events, for tests and for builds without gamepad support
+#[derive(Default)]
+pub struct Fake {
+	pub events: VecDeque<Event>,
+}
+
+impl Backend for Fake {
+	fn poll(&mut self) -> Vec<Event> {
+		self.events.drain(..).collect()
+	}
+}
+
+impl PadButton {
+	pub const ALL: [PadButton; 16] = [
+		PadButton::South,
+		PadButton::East,
+		PadButton::North,
+		PadButton::West,
+		PadButton::LeftTrigger,
+		PadButton::LeftTrigger2,
+		PadButton::RightTrigger,
+		PadButton::RightTrigger2,
+		PadButton::Select,
+		PadButton::Start,
+		PadButton::LeftThumb,
+		PadButton::RightThumb,
+		PadButton::DPadUp,
+		PadButton::DPadDown,
+		PadButton::DPadLeft,
+		PadButton::DPadRight,
+	];
+}
+
+#[cfg(feature = "gamepad")]
+pub struct Gilrs(gilrs::Gilrs);
+
+#[cfg(feature = "gamepad")]
+impl Backend for Gilrs {
+	fn poll(&mut self) -> Vec<Event> {
+		let mut events = vec![];
+		while let Some(gilrs::Event { event, .. }) = self.0.next_event() {
+			match event {
+				gilrs::EventType::ButtonPressed(button, _) => {
+					events.extend(pad_button(button).map(Event::Pressed))
+				}
+				gilrs::EventType::ButtonReleased(button, _) => {
+					events.extend(pad_button(button).map(Event::Released))
+				}
+				gilrs::EventType::AxisChanged(axis, value, _) => {
+					let axis = match axis {
+						gilrs::Axis::LeftStickX => PadAxis::LeftStickX,
+						gilrs::Axis::LeftStickY => PadAxis::LeftStickY,
+						gilrs::Axis::RightStickX => PadAxis::RightStickX,
+						gilrs::Axis::RightStickY => PadAxis::RightStickY,
+						_ => continue,
+					};
+					events.push(Event::Axis(axis, value));
+				}
+				// let go of everything so nothing stays held after unplugging
+				gilrs::EventType::Disconnected => {
+					events.extend(PadButton::ALL.iter().map(|button| Event::Released(*button)));
+					for axis in [
+						PadAxis::LeftStickX,
+						PadAxis::LeftStickY,
+						PadAxis::RightStickX,
+						PadAxis::RightStickY,
+					] {
+						events.push(Event::Axis(axis, 0.0));
+					}
+				}
+				_ => {}
+			}
+		}
+		events
+	}
+}
+
+#[cfg(feature = "gamepad")]
+fn pad_button(button: gilrs::Button) -> Option<PadButton> {
+	Some(match button {
+		gilrs::Button::South => PadButton::South,
+		gilrs::Button::East => PadButton::East,
+		gilrs::Button::North => PadButton::North,
+		gilrs::Button::West => PadButton::West,
+		gilrs::Button::LeftTrigger => PadButton::LeftTrigger,
+		gilrs::Button::LeftTrigger2 => PadButton::LeftTrigger2,
+		gilrs::Button::RightTrigger => PadButton::RightTrigger,
+		gilrs::Button::RightTrigger2 => PadButton::RightTrigger2,
+		gilrs::Button::Select => PadButton::Select,
+		gilrs::Button::Start => PadButton::Start,
+		gilrs::Button::LeftThumb => PadButton::LeftThumb,
+		gilrs::Button::RightThumb => PadButton::RightThumb,
+		gilrs::Button::DPadUp => PadButton::DPadUp,
+		gilrs::Button::DPadDown => PadButton::DPadDown,
+		gilrs::Button::DPadLeft => PadButton::DPadLeft,
+		gilrs::Button::DPadRight => PadButton::DPadRight,
+		_ => return None,
+	})
+}
+
+// the real backend when built with the `gamepad` feature, otherwise one that never has events
+pub fn backend() -> Box<dyn Backend> {
+	#[cfg(feature = "gamepad")]
+	match gilrs::Gilrs::new() {
+		Ok(gilrs) => return Box::new(Gilrs(gilrs)),
+		Err(e) => eprintln!("gamepads are unavailable: {}", e),
+	}
+	Box::new(Fake::default())
+}
@@ -0,0 +1,166 @@
+use crate::recipes::Recipes;
+use crate::render::{Color, Textured, Vertex};
+use crate::spells::{Element, Spell, SpellError};
+use crate::sprite::Atlas;
+use crate::vector::Vector;
+
+// a line of text made of differently colored spans
+pub type Line = Vec<(String, Color)>;
+
+pub const WHITE: Color = [1.0; 3];
+pub const GREY: Color = [0.6; 3];
+pub const RED: Color = [1.0, 0.3, 0.3];
+
+pub fn line(text: &str, color: Color) -> Line {
+	vec![(text.to_string(), color)]
+}
+
+// element colors lifted towards white so that dark ones like void stay readable
+fn legible(element: Element) -> Color {
+	element.color().map(|c| c * 0.7 + 0.3)
+}
+
+// appends quads for each line of text, starting with the top left corner at pos;
+// characters are looked up as frames of the font's `glyphs` animation, starting at space
+pub fn text(
+	vertices: &mut Vec<Vertex<Textured>>,
+	font: &Atlas,
+	lines: &[Line],
+	pos: Vector,
+	height: f32,
+) {
+	let glyph = match font.animations.get("glyphs") {
+		Some(animation) => {
+			Vector::new(height * animation.width as f32 / animation.height as f32, height)
+		}
+		None => return,
+	};
+	for (row, line) in lines.iter().enumerate() {
+		let mut x = pos.x;
+		let y = pos.y - row as f32 * height * 1.25;
+		for (string, color) in line {
+			for c in string.chars() {
+				let frame = (c as u32).saturating_sub(' ' as u32);
+				if let Some((uv_min, uv_max)) = font.frame_uv("glyphs", frame) {
+					let color = *color;
+					let vertex = |x: f32, y: f32, u: f32, v: f32| {
+						Vertex::new(x, y, Textured { uv: Vector::new(u, v), color })
+					};
+					let (min, max) = (Vector::new(x, y - glyph.y), Vector::new(x + glyph.x, y));
+					vertices.extend([
+						vertex(min.x, min.y, uv_min.x, uv_max.y),
+						vertex(max.x, min.y, uv_max.x, uv_max.y),
+						vertex(min.x, max.y, uv_min.x, uv_min.y),
+						vertex(max.x, max.y, uv_max.x, uv_min.y),
+						vertex(min.x, max.y, uv_min.x, uv_min.y),
+						vertex(max.x, min.y, uv_max.x, uv_max.y),
+					]);
+				}
+				x += glyph.x;
+			}
+		}
+	}
+}
+
+// the queued elements, what casting them would produce, and the player's resources
+pub fn status(
+	elements: &[Element],
+	spell: &Result<Spell, SpellError>,
+	(health, max_health): (f32, f32),
+	(mana, max_mana): (f32, f32),
+) -> Vec<Line> {
+	let mut lines = vec![
+		line(&format!("health {:.1} / {:.1}", health, max_health), [1.0, 0.4, 0.4]),
+		line(&format!("mana {:.1} / {:.1}", mana, max_mana), [0.4, 0.6, 1.0]),
+	];
+	if elements.is_empty() {
+		lines.push(line("no elements queued", GREY));
+		return lines;
+	}
+
+	let mut queued = line("queued", WHITE);
+	for element in elements {
+		queued.push((format!(" {}", element.name()), legible(*element)));
+	}
+	lines.push(queued);
+
+	let spell = match spell {
+		Ok(spell) => spell,
+		Err(e) => {
+			lines.push(line(&format!("can't cast: {}", e), RED));
+			return lines;
+		}
+	};
+
+	let mut casts = line("casts ", WHITE);
+	if let Some(name) = &spell.name {
+		casts.push((format!("{} ", name), [1.0, 0.85, 0.3]));
+	}
+	casts.push((spell.element.name().to_string(), legible(spell.element)));
+	casts.push((if spell.is_inverted { " (inverted)" } else { "" }.to_string(), GREY));
+	lines.push(casts);
+	let affordable = if spell.cost <= mana { WHITE } else { RED };
+	lines.push(vec![
+		(format!("range {:.1}  speed {:.1}  ", spell.range, spell.speed), WHITE),
+		(format!("cost {:.1}", spell.cost), affordable),
+	]);
+	lines.push(line(
+		&format!(
+			"strength {:.1}  duration {:.1}  area {:.1}",
+			spell.strength, spell.duration, spell.area
+		),
+		WHITE,
+	));
+	if !spell.behaviours.is_empty() {
+		let behaviours: Vec<String> = spell.behaviours.iter().map(|b| b.to_string()).collect();
+		lines.push(line(&behaviours.join("  "), GREY));
+	}
+	lines
+}
+
+// the element the stick points at, with the first element at the top going clockwise
+pub fn radial_choice(stick: Vector) -> Element {
+	let n = Element::ALL.len() as f32;
+	let clockwise = std::f32::consts::FRAC_PI_2 - stick.y.atan2(stick.x);
+	let sector = (clockwise / std::f32::consts::TAU * n + 0.5).floor().rem_euclid(n);
+	Element::ALL[sector as usize]
+}
+
+// element names in a ring around the center, in the order radial_choice picks them
+pub fn radial_menu(
+	vertices: &mut Vec<Vertex<Textured>>,
+	font: &Atlas,
+	center: Vector,
+	selected: Option<Element>,
+) {
+	let n = Element::ALL.len() as f32;
+	for (i, element) in Element::ALL.iter().enumerate() {
+		let angle = i as f32 / n * std::f32::consts::TAU;
+		let height = if selected == Some(*element) { 0.06 } else { 0.04 };
+		let name = element.name();
+		let width = name.len() as f32 * height * 0.75;
+		let pos = center + Vector::new(angle.sin(), angle.cos()) * 0.4;
+		let pos = pos + Vector::new(-width / 2.0, height / 2.0);
+		text(vertices, font, &[line(name, legible(*element))], pos, height);
+	}
+}
+
+// every recipe, with the ones that haven't been cast yet hidden
+pub fn spellbook(recipes: &Recipes, discovered: &[String]) -> Vec<Line> {
+	let mut lines = vec![line(
+		&format!("spellbook ({} / {})", discovered.len(), recipes.recipes.len()),
+		WHITE,
+	)];
+	for recipe in &recipes.recipes {
+		if discovered.contains(&recipe.name) {
+			let mut entry = line(&format!("  {}:", recipe.name), [1.0, 0.85, 0.3]);
+			for element in &recipe.elements {
+				entry.push((format!(" {}", element.name()), legible(*element)));
+			}
+			lines.push(entry);
+		} else {
+			lines.push(line(&format!("  ??? ({} elements)", recipe.elements.len()), GREY));
+		}
+	}
+	lines
+}
@@ -0,0 +1,419 @@
+use crate::gamepad::PadButton;
+use crate::spells::Element;
+use std::collections::{HashMap, HashSet};
+use winit::event::{ElementState, MouseButton, VirtualKeyCode};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Button {
+	Key(VirtualKeyCode),
+	Scancode(u32), // the physical key, regardless of keyboard layout
+	Mouse(MouseButton),
+	Pad(PadButton),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+	MoveLeft,
+	MoveRight,
+	Jump,
+	QueueElement(Element),
+	Cast,
+	ElementMenu, // while held, the right stick picks elements from a radial menu
+	Spellbook,
+}
+
+pub struct Bindings {
+	pub buttons: HashMap<Action, Vec<Button>>,
+}
+
+// collects button events as they arrive, between ticks
+#[derive(Default)]
+pub struct Input {
+	held: HashSet<Button>,
+	presses: HashMap<Button, u32>,
+	releases: HashMap<Button, u32>,
+}
+
+// the state of every button for one tick: what is held at the end of it,
+// and how many times each button went down or up since the previous tick
+#[derive(Default)]
+pub struct Snapshot {
+	held: HashSet<Button>,
+	presses: HashMap<Button, u32>,
+	releases: HashMap<Button, u32>,
+}
+
+const KEYS: [VirtualKeyCode; 163] = {
+	use VirtualKeyCode::*;
+	[
+		Key1,
+		Key2,
+		Key3,
+		Key4,
+		Key5,
+		Key6,
+		Key7,
+		Key8,
+		Key9,
+		Key0,
+		A,
+		B,
+		C,
+		D,
+		E,
+		F,
+		G,
+		H,
+		I,
+		J,
+		K,
+		L,
+		M,
+		N,
+		O,
+		P,
+		Q,
+		R,
+		S,
+		T,
+		U,
+		V,
+		W,
+		X,
+		Y,
+		Z,
+		Escape,
+		F1,
+		F2,
+		F3,
+		F4,
+		F5,
+		F6,
+		F7,
+		F8,
+		F9,
+		F10,
+		F11,
+		F12,
+		F13,
+		F14,
+		F15,
+		F16,
+		F17,
+		F18,
+		F19,
+		F20,
+		F21,
+		F22,
+		F23,
+		F24,
+		Snapshot,
+		Scroll,
+		Pause,
+		Insert,
+		Home,
+		Delete,
+		End,
+		PageDown,
+		PageUp,
+		Left,
+		Up,
+		Right,
+		Down,
+		Back,
+		Return,
+		Space,
+		Compose,
+		Caret,
+		Numlock,
+		Numpad0,
+		Numpad1,
+		Numpad2,
+		Numpad3,
+		Numpad4,
+		Numpad5,
+		Numpad6,
+		Numpad7,
+		Numpad8,
+		Numpad9,
+		NumpadAdd,
+		NumpadDivide,
+		NumpadDecimal,
+		NumpadComma,
+		NumpadEnter,
+		NumpadEquals,
+		NumpadMultiply,
+		NumpadSubtract,
+		AbntC1,
+		AbntC2,
+		Apostrophe,
+		Apps,
+		Asterisk,
+		At,
+		Ax,
+		Backslash,
+		Calculator,
+		Capital,
+		Colon,
+		Comma,
+		Convert,
+		Equals,
+		Grave,
+		Kana,
+		Kanji,
+		LAlt,
+		LBracket,
+		LControl,
+		LShift,
+		LWin,
+		Mail,
+		MediaSelect,
+		MediaStop,
+		Minus,
+		Mute,
+		MyComputer,
+		NavigateForward,
+		NavigateBackward,
+		NextTrack,
+		NoConvert,
+		OEM102,
+		Period,
+		PlayPause,
+		Plus,
+		Power,
+		PrevTrack,
+		RAlt,
+		RBracket,
+		RControl,
+		RShift,
+		RWin,
+		Semicolon,
+		Slash,
+		Sleep,
+		Stop,
+		Sysrq,
+		Tab,
+		Underline,
+		Unlabeled,
+		VolumeDown,
+		VolumeUp,
+		Wake,
+		WebBack,
+		WebFavorites,
+		WebForward,
+		WebHome,
+		WebRefresh,
+		WebSearch,
+		WebStop,
+		Yen,
+		Copy,
+		Paste,
+		Cut,
+	]
+};
+
+const HEADER: &str = "# action button...
+# buttons are key:<name>, scancode:<number>, mouse:<Left|Right|Middle|number> or pad:<name>
+";
+
+impl Button {
+	pub fn name(self) -> String {
+		match self {
+			Button::Key(key) => format!("key:{:?}", key),
+			Button::Scancode(code) => format!("scancode:{}", code),
+			Button::Mouse(MouseButton::Other(n)) => format!("mouse:{}", n),
+			Button::Mouse(button) => format!("mouse:{:?}", button),
+			Button::Pad(button) => format!("pad:{:?}", button),
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Button> {
+		let (kind, value) = name.split_once(':')?;
+		match kind {
+			"key" => KEYS
+				.iter()
+				.find(|key| format!("{:?}", key) == value)
+				.map(|key| Button::Key(*key)),
+			"scancode" => value.parse().ok().map(Button::Scancode),
+			"mouse" => match value {
+				"Left" => Some(Button::Mouse(MouseButton::Left)),
+				"Right" => Some(Button::Mouse(MouseButton::Right)),
+				"Middle" => Some(Button::Mouse(MouseButton::Middle)),
+				_ => value.parse().ok().map(|n| Button::Mouse(MouseButton::Other(n))),
+			},
+			"pad" => PadButton::ALL
+				.iter()
+				.find(|button| format!("{:?}", button) == value)
+				.map(|button| Button::Pad(*button)),
+			_ => None,
+		}
+	}
+}
+
+impl Action {
+	pub fn all() -> Vec<Action> {
+		let mut actions = vec![Action::MoveLeft, Action::MoveRight, Action::Jump];
+		actions.extend(Element::ALL.iter().map(|element| Action::QueueElement(*element)));
+		actions.extend([Action::Cast, Action::ElementMenu, Action::Spellbook]);
+		actions
+	}
+
+	pub fn name(self) -> String {
+		match self {
+			Action::MoveLeft => "move_left".to_string(),
+			Action::MoveRight => "move_right".to_string(),
+			Action::Jump => "jump".to_string(),
+			Action::QueueElement(element) => format!("queue_{}", element.name()),
+			Action::Cast => "cast".to_string(),
+			Action::ElementMenu => "element_menu".to_string(),
+			Action::Spellbook => "spellbook".to_string(),
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Action> {
+		Action::all().into_iter().find(|action| action.name() == name)
+	}
+}
+
+impl Input {
+	pub fn update(&mut self, button: Button, state: ElementState) {
+		match state {
+			// key repeat sends more presses while held, which aren't new presses
+			ElementState::Pressed => {
+				if self.held.insert(button) {
+					*self.presses.entry(button).or_default() += 1;
+				}
+			}
+			ElementState::Released => {
+				if self.held.remove(&button) {
+					*self.releases.entry(button).or_default() += 1;
+				}
+			}
+		}
+	}
+
+	// takes everything that happened since the last sample
+	pub fn sample(&mut self) -> Snapshot {
+		Snapshot {
+			held: self.held.clone(),
+			presses: std::mem::take(&mut self.presses),
+			releases: std::mem::take(&mut self.releases),
+		}
+	}
+}
+
+impl Snapshot {
+	pub fn held(&self, button: Button) -> bool {
+		self.held.contains(&button)
+	}
+
+	pub fn presses(&self, button: Button) -> u32 {
+		self.presses.get(&button).copied().unwrap_or(0)
+	}
+
+	pub fn releases(&self, button: Button) -> u32 {
+		self.releases.get(&button).copied().unwrap_or(0)
+	}
+
+	pub fn action_held(&self, bindings: &Bindings, action: Action) -> bool {
+		bindings.get(action).iter().any(|button| self.held(*button))
+	}
+
+	pub fn action_presses(&self, bindings: &Bindings, action: Action) -> u32 {
+		bindings.get(action).iter().map(|button| self.presses(*button)).sum()
+	}
+
+	pub fn action_releases(&self, bindings: &Bindings, action: Action) -> u32 {
+		bindings.get(action).iter().map(|button| self.releases(*button)).sum()
+	}
+}
+
+impl Bindings {
+	pub fn new() -> Bindings {
+		use VirtualKeyCode::*;
+		let mut buttons = HashMap::from([
+			(
+				Action::MoveLeft,
+				vec![Button::Key(A), Button::Key(Left), Button::Pad(PadButton::DPadLeft)],
+			),
+			(
+				Action::MoveRight,
+				vec![Button::Key(D), Button::Key(Right), Button::Pad(PadButton::DPadRight)],
+			),
+			(Action::Jump, vec![Button::Key(Space), Button::Pad(PadButton::South)]),
+			(
+				Action::Cast,
+				vec![Button::Mouse(MouseButton::Left), Button::Pad(PadButton::RightTrigger2)],
+			),
+			(Action::ElementMenu, vec![Button::Pad(PadButton::LeftTrigger2)]),
+			(Action::Spellbook, vec![Button::Key(Tab), Button::Pad(PadButton::Select)]),
+		]);
+		let keys = [Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0];
+		for (element, key) in Element::ALL.iter().zip(keys) {
+			buttons.insert(Action::QueueElement(*element), vec![Button::Key(key)]);
+		}
+		// the four most common elements are also on the face and shoulder buttons
+		for (element, button) in [
+			(Element::Earth, PadButton::LeftTrigger),
+			(Element::Water, PadButton::West),
+			(Element::Air, PadButton::East),
+			(Element::Fire, PadButton::North),
+		] {
+			buttons.get_mut(&Action::QueueElement(element)).unwrap().push(Button::Pad(button));
+		}
+		Bindings { buttons }
+	}
+
+	// each line is an action followed by any number of buttons, like `jump key:Space key:W`;
+	// actions that aren't mentioned keep their default bindings
+	pub fn load(path: &str) -> Result<Bindings, String> {
+		let file = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+		let mut bindings = Bindings::new();
+		for (i, line) in file.lines().enumerate() {
+			let words: Vec<&str> = line.split_whitespace().collect();
+			if words.is_empty() || words[0].starts_with('#') {
+				continue;
+			}
+			let action = Action::from_name(words[0])
+				.ok_or_else(|| format!("{}:{}: unknown action `{}`", path, i + 1, words[0]))?;
+			let buttons = words[1..]
+				.iter()
+				.map(|word| {
+					Button::from_name(word)
+						.ok_or_else(|| format!("{}:{}: unknown button `{}`", path, i + 1, word))
+				})
+				.collect::<Result<Vec<Button>, String>>()?;
+			bindings.buttons.insert(action, buttons);
+		}
+		Ok(bindings)
+	}
+
+	pub fn save(&self, path: &str) -> Result<(), String> {
+		let mut file = String::from(HEADER);
+		for action in Action::all() {
+			file += &action.name();
+			for button in self.get(action) {
+				file += " ";
+				file += &button.name();
+			}
+			file += "\n";
+		}
+		std::fs::write(path, file).map_err(|e| format!("{}: {}", path, e))
+	}
+
+	pub fn get(&self, action: Action) -> &[Button] {
+		self.buttons.get(&action).map_or(&[], |buttons| buttons)
+	}
+
+	// binds the button to the action, removing it from any other action first
+	pub fn bind(&mut self, action: Action, button: Button) {
+		for buttons in self.buttons.values_mut() {
+			buttons.retain(|b| *b != button);
+		}
+		self.buttons.entry(action).or_default().push(button);
+	}
+
+	pub fn clear(&mut self, action: Action) {
+		self.buttons.remove(&action);
+	}
+}
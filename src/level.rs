@@ -0,0 +1,185 @@
+use crate::materials::Material;
+use crate::platforms::Motion;
+use crate::render::Color;
+use crate::script::Program;
+use crate::triggers::Kind;
+use crate::vector::Vector;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+pub struct Block {
+	pub pos: Vector,  // center
+	pub size: Vector, // width and height
+	pub material: Material,
+	pub color: Color,
+	pub motion: Option<Motion>, // for moving platforms
+	pub script: Option<Rc<Program>>,
+}
+
+pub struct Trigger {
+	pub pos: Vector,
+	pub size: Vector,
+	pub kind: Kind,
+	pub script: Option<Rc<Program>>,
+}
+
+pub struct Level {
+	pub spawn: Vector,
+	pub ambient: Color, // the light where no lights reach
+	pub ground: Vec<Block>,
+	pub targets: Vec<(Vector, Vector)>, // centers and sizes
+	pub triggers: Vec<Trigger>,
+}
+
+pub const AMBIENT: Color = [0.15, 0.15, 0.25]; // for levels that don't set their own
+
+const HEADER: &str = "# spawn x y
+# ambient r g b
+# ground x y width height material [r g b] [motion] [script path]
+# target x y width height
+# trigger x y width height kind [value] [script path]
+# positions are centers, colors default to the material's,
+# trigger kinds are `exit path`, `checkpoint`, `damage amount` and `switch element`,
+# platforms move by `path speed dx dy [dx dy]...` or `oscillate dx dy period`
+";
+
+// the words before `script` and the script named after it, loading each program only once
+fn script<'a, 'b>(
+	words: &'a [&'b str],
+	programs: &mut HashMap<&'b str, Rc<Program>>,
+	error: impl Fn(String) -> String,
+) -> Result<(&'a [&'b str], Option<Rc<Program>>), String> {
+	let (words, script) =
+		words.split_at(words.iter().position(|word| *word == "script").unwrap_or(words.len()));
+	let program = match script {
+		[] => None,
+		[_, path] => match programs.get(path) {
+			Some(program) => Some(program.clone()),
+			None => {
+				let program = Rc::new(Program::load(path)?);
+				programs.insert(path, program.clone());
+				Some(program)
+			}
+		},
+		_ => return Err(error("expected `script path`".to_string())),
+	};
+	Ok((words, program))
+}
+
+pub fn numbers(words: &[&str]) -> Result<Vec<f32>, String> {
+	words.iter().map(|word| word.parse().map_err(|_| format!("bad number `{}`", word))).collect()
+}
+
+impl Level {
+	pub fn load(path: &str) -> Result<Level, String> {
+		let file = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+		let mut level = Level {
+			spawn: Vector::new(0.0, 0.0),
+			ambient: AMBIENT,
+			ground: vec![],
+			targets: vec![],
+			triggers: vec![],
+		};
+		let mut programs = HashMap::new();
+		for (i, line) in file.lines().enumerate() {
+			let error = |message: String| format!("{}:{}: {}", path, i + 1, message);
+			let words: Vec<&str> = line.split('#').next().unwrap().split_whitespace().collect();
+			match words.as_slice() {
+				[] => {}
+				["spawn", rest @ ..] => match numbers(rest).map_err(error)?.as_slice() {
+					[x, y] => level.spawn = Vector::new(*x, *y),
+					_ => return Err(error("expected `spawn x y`".to_string())),
+				},
+				["ambient", rest @ ..] => match numbers(rest).map_err(error)?.as_slice() {
+					[r, g, b] => level.ambient = [*r, *g, *b],
+					_ => return Err(error("expected `ambient r g b`".to_string())),
+				},
+				["ground", x, y, width, height, material, rest @ ..] => {
+					let n = numbers(&[x, y, width, height]).map_err(error)?;
+					let (rest, script) = script(rest, &mut programs, error)?;
+					let moves =
+						rest.iter().position(|word| ["path", "oscillate"].contains(word));
+					let (color, motion) = rest.split_at(moves.unwrap_or(rest.len()));
+					let motion = match motion {
+						[] => None,
+						_ => Some(Motion::parse(motion).map_err(error)?),
+					};
+					let material = Material::from_name(material)
+						.ok_or_else(|| error(format!("unknown material `{}`", material)))?;
+					let color = match numbers(color).map_err(error)?.as_slice() {
+						[] => material.color(),
+						[r, g, b] => [*r, *g, *b],
+						_ => return Err(error("expected a color as `r g b`".to_string())),
+					};
+					level.ground.push(Block {
+						pos: Vector::new(n[0], n[1]),
+						size: Vector::new(n[2], n[3]),
+						material,
+						color,
+						motion,
+						script,
+					});
+				}
+				["target", rest @ ..] => match numbers(rest).map_err(error)?.as_slice() {
+					[x, y, width, height] => {
+						level.targets.push((Vector::new(*x, *y), Vector::new(*width, *height)))
+					}
+					_ => return Err(error("expected `target x y width height`".to_string())),
+				},
+				["trigger", x, y, width, height, rest @ ..] => {
+					let n = numbers(&[x, y, width, height]).map_err(error)?;
+					let (kind, script) = script(rest, &mut programs, error)?;
+					level.triggers.push(Trigger {
+						pos: Vector::new(n[0], n[1]),
+						size: Vector::new(n[2], n[3]),
+						kind: Kind::parse(kind).map_err(error)?,
+						script,
+					});
+				}
+				[word, ..] => return Err(error(format!("unknown kind `{}`", word))),
+			}
+		}
+		Ok(level)
+	}
+
+	pub fn save(&self, path: &str) -> Result<(), String> {
+		let mut file = String::from(HEADER);
+		file += &format!("spawn {} {}\n", self.spawn.x, self.spawn.y);
+		let [r, g, b] = self.ambient;
+		file += &format!("ambient {} {} {}\n", r, g, b);
+		for block in &self.ground {
+			file += &format!(
+				"ground {} {} {} {} {}",
+				block.pos.x,
+				block.pos.y,
+				block.size.x,
+				block.size.y,
+				block.material.name()
+			);
+			if block.color != block.material.color() {
+				file += &format!(" {} {} {}", block.color[0], block.color[1], block.color[2]);
+			}
+			if let Some(motion) = &block.motion {
+				file += &format!(" {}", motion);
+			}
+			if let Some(program) = &block.script {
+				file += &format!(" script {}", program.path);
+			}
+			file += "\n";
+		}
+		for (pos, size) in &self.targets {
+			file += &format!("target {} {} {} {}\n", pos.x, pos.y, size.x, size.y);
+		}
+		for trigger in &self.triggers {
+			file += &format!(
+				"trigger {} {} {} {} {}",
+				trigger.pos.x, trigger.pos.y, trigger.size.x, trigger.size.y, trigger.kind
+			);
+			if let Some(program) = &trigger.script {
+				file += &format!(" script {}", program.path);
+			}
+			file += "\n";
+		}
+		std::fs::write(path, file).map_err(|e| format!("{}: {}", path, e))
+	}
+}
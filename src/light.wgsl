@@ -0,0 +1,29 @@
+struct VertexInput {
+	[[location(0)]] pos: vec2<f32>;
+	[[location(1)]] offset: vec2<f32>;
+	[[location(2)]] radius: f32;
+	[[location(3)]] color: vec3<f32>;
+};
+
+struct VertexOutput {
+	[[builtin(position)]] pos: vec4<f32>;
+	[[location(0)]] offset: vec2<f32>;
+	[[location(1)]] radius: f32;
+	[[location(2)]] color: vec3<f32>;
+};
+
+[[stage(vertex)]]
+fn vertex(input: VertexInput) -> VertexOutput {
+	var output: VertexOutput;
+	output.pos = vec4<f32>(input.pos, 0.0, 1.0);
+	output.offset = input.offset;
+	output.radius = input.radius;
+	output.color = input.color;
+	return output;
+}
+
+[[stage(fragment)]]
+fn fragment(input: VertexOutput) -> [[location(0)]] vec4<f32> {
+	let falloff = max(1.0 - length(input.offset) / input.radius, 0.0);
+	return vec4<f32>(input.color * falloff * falloff, 1.0);
+}
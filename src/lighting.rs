@@ -0,0 +1,173 @@
+use crate::render::{Color, State, Texture, Textured, Vertex};
+use crate::vector::Vector;
+
+#[derive(Clone, Debug)]
+pub struct Light {
+	pub pos: Vector, // follows the object with the same id, if there is one
+	pub color: Color,
+	pub radius: f32,
+	pub lifetime: f32, // seconds left once detached, infinite while attached
+}
+
+#[repr(C)]
+pub struct LightData {
+	pub offset: Vector, // from the light's center, in world units
+	pub radius: f32,
+	pub color: Color,
+}
+
+pub struct Lightmap {
+	pub target: Texture,
+	pub size: (u32, u32),
+}
+
+// seconds a detached light takes to fade out at the end of its lifetime
+const FADE: f32 = 0.5;
+
+impl Light {
+	pub fn new(color: Color, radius: f32) -> Light {
+		Light { pos: Vector::new(0.0, 0.0), color, radius, lifetime: f32::INFINITY }
+	}
+
+	pub fn brightness(&self) -> f32 {
+		(self.lifetime / FADE).min(1.0)
+	}
+}
+
+fn cross(a: Vector, b: Vector) -> f32 {
+	a.x * b.y - a.y * b.x
+}
+
+// distance along the ray to the segment, if it hits
+fn ray_segment(origin: Vector, dir: Vector, a: Vector, b: Vector) -> Option<f32> {
+	let edge = b - a;
+	let denominator = cross(dir, edge);
+	if denominator == 0.0 {
+		return None;
+	}
+	let t = cross(a - origin, edge) / denominator;
+	let u = cross(a - origin, dir) / denominator;
+	if t >= 0.0 && (0.0..=1.0).contains(&u) {
+		Some(t)
+	} else {
+		None
+	}
+}
+
+// the region lit by a light, as a fan of points around its center, with
+// occluders given as the min and max corners of axis aligned boxes
+pub fn visibility(center: Vector, radius: f32, occluders: &[(Vector, Vector)]) -> Vec<Vector> {
+	let bounds = (center - radius, center + radius);
+	let mut segments = vec![];
+	for &(min, max) in occluders.iter().chain(std::iter::once(&bounds)) {
+		if min.x > bounds.1.x || max.x < bounds.0.x || min.y > bounds.1.y || max.y < bounds.0.y {
+			continue;
+		}
+		let corners = [min, Vector::new(max.x, min.y), max, Vector::new(min.x, max.y)];
+		for i in 0..4 {
+			segments.push((corners[i], corners[(i + 1) % 4]));
+		}
+	}
+
+	let mut angles = vec![];
+	for (a, _) in &segments {
+		let angle = (a.y - center.y).atan2(a.x - center.x);
+		angles.extend([angle - 0.0001, angle, angle + 0.0001]);
+	}
+	angles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+	angles
+		.into_iter()
+		.map(|angle| {
+			let dir = Vector::new(angle.cos(), angle.sin());
+			let t = segments
+				.iter()
+				.filter_map(|(a, b)| ray_segment(center, dir, *a, *b))
+				.fold(f32::INFINITY, f32::min);
+			center + dir * t.min(radius * 2.0)
+		})
+		.collect()
+}
+
+impl Lightmap {
+	pub fn new(state: &State) -> Lightmap {
+		Lightmap {
+			target: state.create_target(),
+			size: (state.config.width, state.config.height),
+		}
+	}
+
+	// accumulates the lights over the ambient color, then multiplies the result onto the view
+	pub fn render(
+		&mut self,
+		state: &State,
+		encoder: &mut wgpu::CommandEncoder,
+		view: &wgpu::TextureView,
+		ambient: Color,
+		lights: &[&Light],
+		occluders: &[(Vector, Vector)],
+	) {
+		if self.size != (state.config.width, state.config.height) {
+			*self = Lightmap::new(state);
+		}
+
+		state.clear(encoder, &self.target.view, ambient);
+		let mut vertices = vec![];
+		for light in lights {
+			let brightness = light.brightness();
+			let color = light.color.map(|c| c * brightness);
+			let fan = visibility(light.pos, light.radius, occluders);
+			let vertex = |p: Vector| {
+				Vertex::new(
+					p.x,
+					p.y,
+					LightData { offset: p - light.pos, radius: light.radius, color },
+				)
+			};
+			for i in 0..fan.len() {
+				vertices.push(vertex(light.pos));
+				vertices.push(vertex(fan[i]));
+				vertices.push(vertex(fan[(i + 1) % fan.len()]));
+			}
+		}
+		let additive = wgpu::BlendComponent {
+			src_factor: wgpu::BlendFactor::One,
+			dst_factor: wgpu::BlendFactor::One,
+			operation: wgpu::BlendOperation::Add,
+		};
+		state.render(
+			encoder,
+			&self.target.view,
+			&state.shaders.get("light.wgsl"),
+			&wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32, 3 => Float32x3],
+			vertices,
+			wgpu::BlendState { color: additive, alpha: additive },
+		);
+
+		let e = state.view_extent();
+		let vertex = |x: f32, y: f32, u: f32, v: f32| {
+			Vertex::new(x, y, Textured { uv: Vector::new(u, v), color: [1.0; 3] })
+		};
+		let multiply = wgpu::BlendComponent {
+			src_factor: wgpu::BlendFactor::Dst,
+			dst_factor: wgpu::BlendFactor::Zero,
+			operation: wgpu::BlendOperation::Add,
+		};
+		state.render_textured(
+			encoder,
+			view,
+			&state.shaders.get("textured.wgsl"),
+			&wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x3],
+			vec![
+				vertex(-e.x, -e.y, 0.0, 1.0),
+				vertex(e.x, -e.y, 1.0, 1.0),
+				vertex(-e.x, e.y, 0.0, 0.0),
+				vertex(e.x, e.y, 1.0, 0.0),
+				vertex(-e.x, e.y, 0.0, 0.0),
+				vertex(e.x, -e.y, 1.0, 1.0),
+			],
+			&self.target,
+			wgpu::BlendState { color: multiply, alpha: multiply },
+		);
+	}
+}
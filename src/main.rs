@@ -1,276 +1,1542 @@
+mod config;
+mod console;
+mod debug;
+mod editor;
+mod fluid;
+mod gamepad;
+mod hud;
+mod input;
+mod level;
+mod lighting;
+mod materials;
 mod object;
+mod particles;
+mod platforms;
+mod random;
+mod reactions;
+mod recipes;
 mod render;
+mod script;
+mod shaders;
 mod spells;
+mod sprite;
+mod terrain;
+mod triggers;
 mod vector;
+mod watch;
 
+use input::{Action, Button};
 use object::{Object, Shape};
-use render::Vertex;
-use std::collections::HashMap;
+use render::{Textured, Vertex};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use vector::Vector;
-use winit::event::{MouseButton, VirtualKeyCode};
-
-const TICKRATE: f32 = 100.0;
-const GRAVITY: f32 = 10.0;
-const JUMP: f32 = 3.0;
-const MOVE: f32 = 1.0;
-const BASE_SPELL_SPEED: f32 = 5.0;
-const BASE_SPELL_RANGE: f32 = 2.0;
-const CEILING_BOUNCE: f32 = -0.01;
-const PLAYER_HEIGHT: f32 = 0.2;
-const GROUND_CHECK: f32 = 0.0001;
+use winit::event::VirtualKeyCode;
+
+const TRAJECTORY_TIME: f32 = 5.0;
+const TRAJECTORY_STEP: f32 = 0.01; // seconds, whatever the tickrate, so the preview costs the same
+const STICK_DEADZONE: f32 = 0.2;
+const TIME_SCALES: [f32; 7] = [0.1, 0.25, 0.5, 1.0, 1.5, 2.0, 4.0]; // that the game can run at
+const MAX_CATCH_UP: f32 = 10.0; // ticks run in one frame at most, so a stall doesn't snowball
+const STATIONARY_TIME: f32 = 3.0; // seconds a stationary spell lasts per point of duration
+const SHARD_SPREAD: f32 = 1.0; // radians either side of the way back out
+const BLOCK_SIZE: f32 = 0.15; // of the block earth builds, per point of area
+const CRATER_SIZE: f32 = 0.3; // of the hole acid and void carve, per point of area
+const MIN_BLOCK: f32 = 0.02; // thinner pieces of carved terrain crumble away
+const SOLIDS_MARGIN: f32 = 0.01; // looked around a moving object for terrain
+const WATER_PER_AREA: f32 = 3.0; // full fluid cells a water spell leaves, per point of area
+const WET: f32 = 0.1; // fluid depth that spells react with
+const REACTED_WATER: f32 = 0.5; // fluid used up when standing water is consumed by a reaction
+const FIRE_REACH: f32 = 0.2; // of the area a fire spell sets alight, per point of area
+const SPREAD_DELAY: f32 = 0.5; // seconds a block burns before it lights its neighbours
+const SPREAD_REACH: f32 = 0.02; // how close a neighbour has to be to catch
+const BURNING_TINT: render::Color = [1.0, 0.6, 0.4];
+const WATER_SLOW: f32 = 0.5; // of the player's walking speed when in water
+const BUOYANCY: f32 = 14.0; // upwards acceleration when fully under water
+const WATER_DRAG: f32 = 0.97; // of the player's vertical velocity kept each tick in water
+const SWIM: f32 = 1.5; // upwards velocity when jumping in water
+const BINDINGS_PATH: &str = "assets/bindings.txt";
+const RECIPES_PATH: &str = "assets/recipes.txt";
+const REACTIONS_PATH: &str = "assets/reactions.txt";
+const LEVEL_PATH: &str = "assets/level.txt";
+const CONFIG_PATH: &str = "assets/config.txt";
+const TARGET_COLOR: render::Color = [0.8, 0.3, 0.2];
 
 type Id = usize;
 type System<Data> = HashMap<Id, Data>;
 
+// something that a spell can react with
+#[derive(Clone, Copy)]
+enum Reactant {
+	Spell(Id),
+	Water, // standing water where the spell is
+}
+
 struct World {
+	config: config::Config,
+	level_path: String,
 	player_id: Id,
+	spawn: Vector, // where the player comes back to, moved by checkpoints
 	ground_ids: Vec<Id>,
+	terrain: terrain::Grid,        // ground_ids by where they are
+	terrain_changed: BTreeSet<Id>, // blocks whose quads the renderer has to redo
+	target_ids: Vec<Id>,           // what homing spells seek out
 	total_ids: Id,
 
 	objects: System<Object>,
 	colors: System<render::Color>,
 	spells: System<spells::Spell>,
+	sprites: System<sprite::Sprite>,
+	emitters: System<particles::Emitter>,
+	lights: System<lighting::Light>,
+	materials: System<materials::Material>, // ground without one is stone
+	burning: System<f32>,                   // seconds each burning block has been alight
+	previous: System<Vector>, // where each object was before the last tick, to draw in between
+	scripts: System<script::Instance>,
+	triggers: System<triggers::Trigger>,
+	platforms: System<platforms::Platform>, // ground that moves by itself
+
+	particles: particles::Particles,
+	fluid: fluid::Fluid,
+	ambient: render::Color,
 
 	elements: Vec<spells::Element>,
-	buttons: HashMap<Button, ButtonState>,
+	health: f32,
+	mana: f32,
+	recipes: recipes::Recipes,
+	reactions: reactions::Reactions,
+	discovered: Vec<String>, // names of recipes that have been cast, in order
+	show_spellbook: bool,
+	show_debug: bool,
+	input: input::Input,
+	snapshot: input::Snapshot, // the input for the current tick
 	mouse: Vector,
+	left_stick: Vector,
+	right_stick: Vector,
+	menu_choice: Option<spells::Element>, // highlighted in the radial menu
+	bindings: input::Bindings,
+	rebinding: Option<usize>, // index into Action::all() of the action waiting for a button
+	editor: Option<editor::Editor>, // while editing the level instead of playing
+	console: Option<console::Console>,
+	paused: bool,
+	steps: u32,      // single ticks to run while paused
+	time_scale: f32, // of real time that passes in game
 }
 
-#[derive(PartialEq, Eq, Hash)]
-enum Button {
-	Key(VirtualKeyCode),
-	Mouse(MouseButton),
-}
+impl World {
+	fn new(config: config::Config) -> World {
+		World {
+			level_path: LEVEL_PATH.to_string(),
+			player_id: 0,
+			spawn: Vector::new(0.0, 0.0),
+			ground_ids: vec![],
+			terrain: terrain::Grid::default(),
+			terrain_changed: BTreeSet::new(),
+			target_ids: vec![],
+			total_ids: 1,
 
-#[derive(Clone, Copy, Debug)]
-enum ButtonState {
-	NotHeld,
-	HeldUnreadPress,
-	HeldReadPress,
-}
+			objects: HashMap::from([(
+				0,
+				Object::new(
+					0.0,
+					0.0,
+					Shape::Aabb(Vector::new(config.player_height / 2.0, config.player_height)),
+				),
+			)]),
+			colors: HashMap::from([(0, [1.0; 3])]),
+			spells: HashMap::new(),
+			sprites: HashMap::from([(0, sprite::Sprite::new("player_idle"))]),
+			emitters: HashMap::new(),
+			lights: HashMap::from([(0, lighting::Light::new([0.5, 0.45, 0.4], 0.6))]),
+			materials: HashMap::new(),
+			burning: HashMap::new(),
+			previous: HashMap::new(),
+			scripts: HashMap::new(),
+			triggers: HashMap::new(),
+			platforms: HashMap::new(),
 
-impl World {
-	fn is_button_held(&self, button: Button) -> bool {
-		match self.buttons.get(&button) {
-			Some(ButtonState::HeldUnreadPress) | Some(ButtonState::HeldReadPress) => true,
-			_ => false,
+			particles: particles::Particles::new(0x5eed),
+			fluid: fluid::Fluid::default(),
+			ambient: level::AMBIENT,
+
+			elements: vec![],
+			health: config.max_health,
+			mana: config.max_mana,
+			recipes: recipes::Recipes::load(RECIPES_PATH).unwrap_or_else(|e| {
+				eprintln!("{}", e);
+				recipes::Recipes { recipes: vec![] }
+			}),
+			reactions: reactions::Reactions::load(REACTIONS_PATH).unwrap_or_else(|e| {
+				eprintln!("{}", e);
+				reactions::Reactions { reactions: vec![] }
+			}),
+			discovered: vec![],
+			show_spellbook: false,
+			show_debug: false,
+			input: input::Input::default(),
+			snapshot: input::Snapshot::default(),
+			mouse: Vector::new(0.0, 0.0),
+			left_stick: Vector::new(0.0, 0.0),
+			right_stick: Vector::new(0.0, 0.0),
+			menu_choice: None,
+			bindings: input::Bindings::load(BINDINGS_PATH).unwrap_or_else(|e| {
+				eprintln!("{}", e);
+				input::Bindings::new()
+			}),
+			rebinding: None,
+			editor: None,
+			console: None,
+			paused: false,
+			steps: 0,
+			time_scale: 1.0,
+			config,
+		}
+	}
+
+	// swaps in new settings, resizing the player to match
+	fn set_config(&mut self, config: config::Config) {
+		if let Some(player) = self.objects.get_mut(&self.player_id) {
+			let height = config.player_height;
+			player.shape = Shape::Aabb(Vector::new(height / 2.0, height));
 		}
+		self.config = config;
 	}
 
-	fn is_button_pressed(&mut self, button: Button) -> bool {
-		match self.buttons.get(&button) {
-			Some(ButtonState::HeldUnreadPress) => {
-				self.buttons.insert(button, ButtonState::HeldReadPress);
-				true
+	fn is_action_held(&self, action: Action) -> bool {
+		self.snapshot.action_held(&self.bindings, action)
+	}
+
+	fn action_presses(&self, action: Action) -> u32 {
+		self.snapshot.action_presses(&self.bindings, action)
+	}
+
+	fn action_releases(&self, action: Action) -> u32 {
+		self.snapshot.action_releases(&self.bindings, action)
+	}
+
+	// binds released buttons to each action in turn, then saves the bindings
+	fn rebind(&mut self, button: Button) {
+		let actions = Action::all();
+		let index = match self.rebinding {
+			Some(index) => index,
+			None => return,
+		};
+		if button != Button::Key(VirtualKeyCode::Escape) {
+			self.bindings.clear(actions[index]);
+			self.bindings.bind(actions[index], button);
+		}
+		if index + 1 < actions.len() {
+			self.rebinding = Some(index + 1);
+		} else {
+			self.rebinding = None;
+			if let Err(e) = self.bindings.save(BINDINGS_PATH) {
+				eprintln!("{}", e);
 			}
-			_ => false,
 		}
 	}
 
 	fn update_button(&mut self, button: Button, state: winit::event::ElementState) {
-		let old = *self.buttons.get(&button).unwrap_or(&ButtonState::NotHeld);
-		self.buttons.insert(
-			button,
-			match state {
-				winit::event::ElementState::Pressed => match old {
-					ButtonState::NotHeld => ButtonState::HeldUnreadPress,
-					ButtonState::HeldUnreadPress => ButtonState::HeldUnreadPress,
-					ButtonState::HeldReadPress => ButtonState::HeldReadPress,
+		use winit::event::ElementState::*;
+		// typing goes to the console, but releases still reach the game so no key sticks
+		if let Some(mut console) = self.console.take() {
+			if state == Released {
+				self.input.update(button, Released);
+			}
+			if console.key(self, button, state) {
+				self.console = Some(console);
+			}
+			return;
+		}
+		match (self.rebinding, state, button) {
+			(None, Released, Button::Key(VirtualKeyCode::Grave)) => {
+				self.console = Some(console::Console::default());
+				return;
+			}
+			(None, Released, Button::Key(VirtualKeyCode::F1)) => {
+				self.rebinding = Some(0);
+				return;
+			}
+			(None, Released, Button::Key(VirtualKeyCode::F3)) => {
+				self.show_debug = !self.show_debug;
+				return;
+			}
+			(None, Released, Button::Key(VirtualKeyCode::F4)) => {
+				self.paused = !self.paused;
+				return;
+			}
+			// advancing a tick pauses the game if it wasn't already
+			(None, Pressed, Button::Key(VirtualKeyCode::F5)) => {
+				self.paused = true;
+				self.steps += 1;
+				return;
+			}
+			(None, Released, Button::Key(VirtualKeyCode::F6)) => {
+				let slower = TIME_SCALES.iter().rev().find(|scale| **scale < self.time_scale);
+				self.time_scale = *slower.unwrap_or(&TIME_SCALES[0]);
+				return;
+			}
+			(None, Released, Button::Key(VirtualKeyCode::F7)) => {
+				let faster = TIME_SCALES.iter().find(|scale| **scale > self.time_scale);
+				self.time_scale = *faster.unwrap_or(&TIME_SCALES[TIME_SCALES.len() - 1]);
+				return;
+			}
+			(None, Released, Button::Key(VirtualKeyCode::F2)) => {
+				self.editor = match self.editor {
+					Some(_) => None,
+					None => Some(editor::Editor::default()),
+				};
+				return;
+			}
+			(Some(_), Pressed, _) | (Some(_), _, Button::Scancode(_)) => return,
+			(Some(_), Released, _) => {
+				self.input.update(button, Released);
+				self.rebind(button);
+				return;
+			}
+			_ => {}
+		}
+		self.input.update(button, state);
+	}
+
+	// lines reaching just past the player's head and feet, and whether each touches the ground
+	fn probes(&self) -> [(Object, bool); 2] {
+		let player = self.objects.get(&self.player_id).unwrap();
+		let ground_objects = self.solids(player, 0.0);
+		let reach = self.config.player_height / 2.0 + self.config.ground_check;
+		[reach, -reach].map(|dy| {
+			let probe =
+				Object::new(player.pos.x, player.pos.y, Shape::Line(Vector::new(0.0, dy)));
+			let touching = ground_objects.iter().any(|g| object::collide(&probe, g));
+			(probe, touching)
+		})
+	}
+
+	// what the player stands on and spells bounce off, near where the object moves this tick:
+	// the ground and stationary spells
+	fn solids(&self, object: &Object, delta_time: f32) -> Vec<Object> {
+		let (min, max) = object.bounds();
+		let step = object.vel * delta_time;
+		let min = Vector::new(min.x + step.x.min(0.0), min.y + step.y.min(0.0)) - SOLIDS_MARGIN;
+		let max = Vector::new(max.x + step.x.max(0.0), max.y + step.y.max(0.0)) + SOLIDS_MARGIN;
+		self.solids_within(min, max)
+	}
+
+	// terrain in the box, and every stationary spell wherever it is
+	fn solids_within(&self, min: Vector, max: Vector) -> Vec<Object> {
+		let walls = self.spells.iter().filter(|(_, stats)| stats.is_stationary());
+		self.terrain
+			.query(min, max)
+			.iter()
+			.chain(walls.map(|(id, _)| id))
+			.map(|id| self.objects.get(id).unwrap().clone())
+			.collect()
+	}
+
+	fn material(&self, id: Id) -> materials::Material {
+		self.materials.get(&id).copied().unwrap_or(materials::Material::Stone)
+	}
+
+	fn add_ground(&mut self, object: Object, material: materials::Material) -> Id {
+		let id = self.total_ids;
+		self.total_ids += 1;
+		let (min, max) = object.bounds();
+		self.terrain.insert(id, min, max);
+		self.objects.insert(id, object);
+		self.colors.insert(id, material.color());
+		self.materials.insert(id, material);
+		self.sprites.insert(id, sprite::Sprite::new("ground"));
+		self.ground_ids.push(id);
+		self.terrain_changed.insert(id);
+		id
+	}
+
+	// replaces a block's object, keeping the broad-phase in step; a moving platform's whole
+	// route goes along with it
+	fn move_ground(&mut self, id: Id, object: Object) {
+		let old = self.objects.get(&id).unwrap();
+		if let Some(platform) = self.platforms.get_mut(&id) {
+			platform.origin += object.pos - old.pos;
+		}
+		let (min, max) = old.bounds();
+		self.terrain.remove(id, min, max);
+		let (min, max) = object.bounds();
+		self.terrain.insert(id, min, max);
+		self.objects.insert(id, object);
+		self.terrain_changed.insert(id);
+	}
+
+	fn remove_ground(&mut self, id: Id) {
+		let object = self.objects.remove(&id).unwrap();
+		let (min, max) = object.bounds();
+		self.terrain.remove(id, min, max);
+		self.colors.remove(&id);
+		self.sprites.remove(&id);
+		self.materials.remove(&id);
+		self.burning.remove(&id);
+		self.emitters.remove(&id);
+		self.lights.remove(&id);
+		self.scripts.remove(&id);
+		self.platforms.remove(&id);
+		self.ground_ids.retain(|i| *i != id);
+		self.terrain_changed.insert(id);
+	}
+
+	// puts the player at the spawn and adds the level's ground and targets
+	fn load_level(&mut self, level: &level::Level) {
+		self.spawn = level.spawn;
+		self.ambient = level.ambient;
+		let player = self.objects.get_mut(&self.player_id).unwrap();
+		player.pos = level.spawn;
+		player.vel = Vector::new(0.0, 0.0);
+		for block in &level.ground {
+			let object = Object::new(block.pos.x, block.pos.y, Shape::Aabb(block.size));
+			let id = self.add_ground(object, block.material);
+			self.colors.insert(id, block.color);
+			if let Some(motion) = &block.motion {
+				self.platforms.insert(id, platforms::Platform::new(block.pos, motion.clone()));
+			}
+			if let Some(program) = &block.script {
+				self.scripts.insert(id, script::Instance::new(program.clone()));
+			}
+		}
+		for (pos, size) in &level.targets {
+			self.add_target(*pos, *size);
+		}
+		for trigger in &level.triggers {
+			let id = self.total_ids;
+			self.total_ids += 1;
+			let object = Object::new(trigger.pos.x, trigger.pos.y, Shape::Aabb(trigger.size));
+			self.objects.insert(id, object);
+			let kind = trigger.kind.clone();
+			self.triggers.insert(id, triggers::Trigger { kind, inside: vec![] });
+			if let Some(program) = &trigger.script {
+				self.scripts.insert(id, script::Instance::new(program.clone()));
+			}
+		}
+		for id in self.script_ids() {
+			self.run_script(id, "load", &[]);
+		}
+	}
+
+	fn script_ids(&self) -> Vec<Id> {
+		let mut ids: Vec<Id> = self.scripts.keys().copied().collect();
+		ids.sort();
+		ids
+	}
+
+	// runs an event's handler in the script attached to an object, if it has one
+	fn run_script(&mut self, id: Id, event: &str, locals: &[(&str, script::Value)]) {
+		if let Some(mut instance) = self.scripts.remove(&id) {
+			instance.run(self, id, event, locals);
+			// unless the script removed its own object
+			if self.objects.contains_key(&id) {
+				self.scripts.insert(id, instance);
+			}
+		}
+	}
+
+	// swaps in the new version of a script, keeping the variables of objects running it
+	fn reload_script(&mut self, path: &str) -> Result<(), String> {
+		let program = std::rc::Rc::new(script::Program::load(path)?);
+		for instance in self.scripts.values_mut() {
+			if instance.program.path == path {
+				instance.program = program.clone();
+			}
+		}
+		Ok(())
+	}
+
+	// takes an object out of the world, whatever it is
+	fn remove(&mut self, id: Id) -> Result<(), String> {
+		if id == self.player_id {
+			Err("the player can't be removed".to_string())
+		} else if self.ground_ids.contains(&id) {
+			self.remove_ground(id);
+			Ok(())
+		} else if self.spells.contains_key(&id) {
+			self.cancel_spell(id);
+			Ok(())
+		} else {
+			self.objects.remove(&id);
+			self.colors.remove(&id);
+			self.sprites.remove(&id);
+			self.emitters.remove(&id);
+			self.lights.remove(&id);
+			self.scripts.remove(&id);
+			self.triggers.remove(&id);
+			self.target_ids.retain(|i| *i != id);
+			Ok(())
+		}
+	}
+
+	fn add_target(&mut self, pos: Vector, size: Vector) -> Id {
+		let id = self.total_ids;
+		self.total_ids += 1;
+		self.objects.insert(id, Object::new(pos.x, pos.y, Shape::Aabb(size)));
+		self.colors.insert(id, TARGET_COLOR);
+		self.target_ids.push(id);
+		id
+	}
+
+	// swaps the ground and targets for the level's, leaving the player where they are
+	fn reload_level(&mut self, level: &level::Level) {
+		for id in self.ground_ids.clone() {
+			self.remove_ground(id);
+		}
+		for id in std::mem::take(&mut self.target_ids) {
+			self.objects.remove(&id);
+			self.colors.remove(&id);
+		}
+		for id in self.triggers.keys().copied().collect::<Vec<Id>>() {
+			self.remove(id).unwrap();
+		}
+		let player = self.objects.get(&self.player_id).unwrap().clone();
+		self.load_level(level);
+		self.objects.insert(self.player_id, player);
+	}
+
+	// moves on to another level, starting the player at its spawn
+	fn change_level(&mut self, path: &str) -> Result<(), String> {
+		let level = level::Level::load(path)?;
+		self.reload_level(&level);
+		self.level_path = path.to_string();
+		self.respawn();
+		Ok(())
+	}
+
+	fn respawn(&mut self) {
+		let player = self.objects.get_mut(&self.player_id).unwrap();
+		player.pos = self.spawn;
+		player.vel = Vector::new(0.0, 0.0);
+		self.previous.insert(self.player_id, self.spawn);
+		self.health = self.config.max_health;
+	}
+
+	// notices the player and spells going into and out of trigger volumes, tells their scripts
+	// and does whatever each kind of trigger does
+	fn sense(&mut self, delta_time: f32) {
+		let mut ids: Vec<Id> = self.triggers.keys().copied().collect();
+		ids.sort();
+		let mut others: Vec<Id> = self.spells.keys().copied().collect();
+		others.sort();
+		others.insert(0, self.player_id);
+		let mut exit = None;
+		for id in ids {
+			// an earlier trigger's script may have removed this one, or what went into it
+			let Some(object) = self.objects.get(&id) else { continue };
+			let (min, max) = object.bounds();
+			let Some(trigger) = self.triggers.get_mut(&id) else { continue };
+			let inside: Vec<Id> = others
+				.iter()
+				.copied()
+				.filter(|other| {
+					let spell = self.spells.get(other).map(|stats| stats.element);
+					let Some(other) = self.objects.get(other) else { return false };
+					let (other_min, other_max) = other.bounds();
+					trigger.kind.notices(spell)
+						&& object::aabb_aabb(min, max, other_min, other_max)
+				})
+				.collect();
+			let before = std::mem::replace(&mut trigger.inside, inside.clone());
+			let entered: Vec<Id> =
+				inside.iter().filter(|o| !before.contains(o)).copied().collect();
+			let left: Vec<Id> = before.iter().filter(|o| !inside.contains(o)).copied().collect();
+
+			let player_entered = entered.contains(&self.player_id);
+			match trigger.kind.clone() {
+				triggers::Kind::Exit(path) if player_entered => exit = Some(path),
+				triggers::Kind::Checkpoint if player_entered => self.spawn = (min + max) * 0.5,
+				triggers::Kind::Damage(amount) if inside.contains(&self.player_id) => {
+					self.health -= amount * delta_time
+				}
+				_ => {}
+			}
+			for (event, others) in [("enter", entered), ("exit", left)] {
+				for other in others {
+					// a spell that left by landing is gone, so its element is forgotten
+					let element = match self.spells.get(&other) {
+						Some(stats) => stats.element.name(),
+						None => "",
+					};
+					let locals = [
+						("other", script::Value::Number(other as f32)),
+						("element", script::Value::Text(element.to_string())),
+					];
+					self.run_script(id, event, &locals);
+				}
+			}
+		}
+		if self.health <= 0.0 {
+			self.respawn();
+		}
+		if let Some(path) = exit {
+			if let Err(e) = self.change_level(&path) {
+				eprintln!("{}", e);
+			}
+		}
+	}
+
+	// the level as it stands, including whatever spells have done to the ground
+	fn level(&self) -> level::Level {
+		let block = |id: &Id| {
+			let object = self.objects.get(id).unwrap();
+			// the size as loaded, since going through the bounds rounds it a little each save
+			let size = match object.shape {
+				Shape::Aabb(size) => size,
+				Shape::Line(_) => {
+					let (min, max) = object.bounds();
+					max - min
+				}
+			};
+			(object.pos, size)
+		};
+		// sorted so that saving the same world twice writes the same file
+		let mut ground_ids = self.ground_ids.clone();
+		ground_ids.sort_unstable();
+		let mut triggers: Vec<_> = self.triggers.iter().collect();
+		triggers.sort_unstable_by_key(|(id, _)| **id);
+		level::Level {
+			spawn: self.spawn,
+			ambient: self.ambient,
+			ground: ground_ids
+				.iter()
+				.map(|id| {
+					let (pos, size) = block(id);
+					let platform = self.platforms.get(id);
+					level::Block {
+						// platforms are saved from where they set off
+						pos: platform.map_or(pos, |platform| platform.origin),
+						size,
+						material: self.material(*id),
+						color: *self.colors.get(id).unwrap(),
+						motion: platform.map(|platform| platform.motion.clone()),
+						script: self.scripts.get(id).map(|instance| instance.program.clone()),
+					}
+				})
+				.collect(),
+			targets: self.target_ids.iter().map(block).collect(),
+			triggers: triggers
+				.into_iter()
+				.map(|(id, trigger)| {
+					let (pos, size) = block(id);
+					level::Trigger {
+						pos,
+						size,
+						kind: trigger.kind.clone(),
+						script: self.scripts.get(id).map(|instance| instance.program.clone()),
+					}
+				})
+				.collect(),
+		}
+	}
+
+	// sets a block alight if it burns and isn't already
+	fn ignite(&mut self, id: Id) {
+		let material = self.material(id);
+		if material.burn_time().is_none() || self.burning.contains_key(&id) {
+			return;
+		}
+		self.burning.insert(id, 0.0);
+		// melting ice is tinted like anything else that's burning
+		self.terrain_changed.insert(id);
+		if material.spreads_fire() {
+			self.emitters.insert(id, particles::trail(spells::Element::Fire));
+			self.lights.insert(id, lighting::Light::new([1.0, 0.5, 0.1], 0.3));
+		}
+	}
+
+	fn ignite_area(&mut self, min: Vector, max: Vector) {
+		for id in self.terrain.query(min, max) {
+			let (block_min, block_max) = self.objects.get(&id).unwrap().bounds();
+			if object::aabb_aabb(block_min, block_max, min, max) {
+				self.ignite(id);
+			}
+		}
+	}
+
+	fn extinguish(&mut self, id: Id) {
+		self.burning.remove(&id);
+		self.emitters.remove(&id);
+		self.lights.remove(&id);
+		self.terrain_changed.insert(id);
+	}
+
+	// burning blocks light their neighbours, go out under water and, once they've burned
+	// for long enough, turn into whatever their material leaves behind
+	fn burn(&mut self, delta_time: f32) {
+		let mut ids: Vec<Id> = self.burning.keys().copied().collect();
+		ids.sort();
+		for id in ids {
+			// a neighbour may have burned out and taken this one with it
+			let burned = match self.burning.get_mut(&id) {
+				Some(burned) => burned,
+				None => continue,
+			};
+			*burned += delta_time;
+			let burned = *burned;
+			let material = self.material(id);
+			let (min, max) = self.objects.get(&id).unwrap().bounds();
+			let above = Vector::new((min.x + max.x) / 2.0, max.y + fluid::CELL / 2.0);
+			if self.fluid.depth(above) > WET {
+				self.extinguish(id);
+				continue;
+			}
+			if material.spreads_fire() && burned >= SPREAD_DELAY {
+				self.ignite_area(min - SPREAD_REACH, max + SPREAD_REACH);
+			}
+			if burned < material.burn_time().unwrap() {
+				continue;
+			}
+			self.extinguish(id);
+			match material.remains() {
+				materials::Remains::Nothing => self.remove_ground(id),
+				materials::Remains::Material(remains) => {
+					self.materials.insert(id, remains);
+					self.colors.insert(id, remains.color());
+				}
+				materials::Remains::Water => {
+					self.remove_ground(id);
+					let mut y = min.y + fluid::CELL / 2.0;
+					while y < max.y {
+						let mut x = min.x + fluid::CELL / 2.0;
+						while x < max.x {
+							self.fluid.add(Vector::new(x, y), 1.0);
+							x += fluid::CELL;
+						}
+						y += fluid::CELL;
+					}
+				}
+			}
+		}
+	}
+
+	// removes the box from the ground, splitting blocks it only partly covers
+	fn carve(&mut self, min: Vector, max: Vector) {
+		for id in self.terrain.query(min, max) {
+			let (block_min, block_max) = self.objects.get(&id).unwrap().bounds();
+			if !object::aabb_aabb(block_min, block_max, min, max) {
+				continue;
+			}
+			// the pieces carry on being whatever the block was
+			let material = self.material(id);
+			let pos = self.objects.get(&id).unwrap().pos;
+			let color = self.colors.get(&id).copied();
+			let script = self.scripts.get(&id).cloned();
+			let burning = self.burning.get(&id).copied();
+			let light = self.lights.get(&id).cloned();
+			let emitter = self.emitters.get(&id).cloned();
+			let platform = self
+				.platforms
+				.get(&id)
+				.map(|platform| (platform.origin - pos, platform.motion.clone(), platform.time));
+			self.remove_ground(id);
+			for (piece_min, piece_max) in terrain::subtract(block_min, block_max, min, max) {
+				let size = piece_max - piece_min;
+				if size.x < MIN_BLOCK || size.y < MIN_BLOCK {
+					continue;
+				}
+				let center = (piece_min + piece_max) * 0.5;
+				let piece = self
+					.add_ground(Object::new(center.x, center.y, Shape::Aabb(size)), material);
+				if let Some(color) = color {
+					self.colors.insert(piece, color);
+				}
+				if let Some(script) = &script {
+					self.scripts.insert(piece, script.clone());
+				}
+				if let Some(burning) = burning {
+					self.burning.insert(piece, burning);
+				}
+				if let Some(light) = &light {
+					self.lights.insert(piece, light.clone());
+				}
+				if let Some(emitter) = &emitter {
+					self.emitters.insert(piece, emitter.clone());
+				}
+				// each piece follows the block's route from where it sits on the block
+				if let Some((offset, motion, time)) = &platform {
+					let mut moving = platforms::Platform::new(center + *offset, motion.clone());
+					moving.time = *time;
+					self.platforms.insert(piece, moving);
+				}
+			}
+		}
+	}
+
+	// everything else that a spell can hit, apart from the player and other spells,
+	// which react with it instead
+	fn entities(&self) -> Vec<(Id, Object)> {
+		self.objects
+			.iter()
+			.filter(|(id, _)| {
+				**id != self.player_id
+					&& !self.ground_ids.contains(id)
+					&& !self.spells.contains_key(id)
+					&& !self.triggers.contains_key(id)
+			})
+			.map(|(id, object)| (*id, object.clone()))
+			.collect()
+	}
+
+	fn targets(&self) -> Vec<Vector> {
+		self.target_ids.iter().map(|id| self.objects.get(id).unwrap().pos).collect()
+	}
+
+	fn spawn_spell(&mut self, object: Object, stats: spells::Spell) {
+		let id = self.total_ids;
+		self.total_ids += 1;
+		self.objects.insert(id, object);
+		self.colors.insert(id, [1.0; 3]);
+		self.sprites.insert(id, sprite::Sprite::new(&format!("spell_{}", stats.element.name())));
+		self.emitters.insert(id, particles::trail(stats.element));
+		if stats.light > 0.0 {
+			let color = match stats.element {
+				spells::Element::Radiance => [1.0, 0.9, 1.0],
+				spells::Element::Fire => [1.0, 0.5, 0.1],
+				element => element.color(),
+			};
+			self.lights.insert(id, lighting::Light::new(color, stats.light));
+		}
+		self.spells.insert(id, stats);
+	}
+
+	// removes a spell that hit something, leaving behind whatever its element does on impact
+	fn end_spell(&mut self, id: Id) {
+		let spell = self.spells.remove(&id).unwrap();
+		let object = self.objects.remove(&id).unwrap();
+		self.colors.remove(&id);
+		self.sprites.remove(&id);
+		self.emitters.remove(&id);
+		let (preset, count) = particles::impact(spell.element);
+		self.particles.emit(object.pos, Vector::new(0.0, 0.0), preset, count);
+		// scripted objects the spell landed on get to react to it
+		let (min, max) = object.bounds();
+		let (min, max) = (min - SOLIDS_MARGIN, max + SOLIDS_MARGIN);
+		for hit in self.script_ids() {
+			let (hit_min, hit_max) = match self.objects.get(&hit) {
+				Some(hit) => hit.bounds(),
+				None => continue,
+			};
+			if object::aabb_aabb(min, max, hit_min, hit_max) {
+				let element = script::Value::Text(spell.element.name().to_string());
+				self.run_script(hit, "hit", &[("element", element)]);
+			}
+		}
+		// radiance lingers where it lands and fire flares briefly
+		match (spell.element, self.lights.get_mut(&id)) {
+			(spells::Element::Radiance, Some(light)) => light.lifetime = spell.duration * 3.0,
+			(spells::Element::Fire, Some(light)) => {
+				light.radius *= 1.5;
+				light.lifetime = 0.3;
+			}
+			_ => {
+				self.lights.remove(&id);
+			}
+		}
+		// shards fan back out the way the spell came from
+		let shards = spell.shards();
+		let back = (object.vel * -1.0).normalized().unwrap_or(Vector::new(0.0, 1.0));
+		for i in 0..shards {
+			let angle = back.y.atan2(back.x)
+				+ SHARD_SPREAD * (2.0 * i as f32 / (shards - 1).max(1) as f32 - 1.0);
+			let mut stats = spell.clone();
+			stats.behaviours.retain(|behaviour| {
+				!matches!(behaviour, spells::Behaviour::Split(_) | spells::Behaviour::Stationary)
+			});
+			stats.range *= 0.5;
+			stats.light = 0.0;
+			stats.dist_traveled = 0.0;
+			stats.age = 0.0;
+			let speed = self.config.base_spell_speed * spell.speed.max(1.0) * 0.5;
+			let shard = Object {
+				pos: object.pos,
+				vel: Vector::new(angle.cos(), angle.sin()) * speed,
+				shape: Shape::Aabb(Vector::new(0.05, 0.05)),
+			};
+			self.spawn_spell(shard, stats);
+		}
+		// earth builds a block where it lands, acid and void eat a hole, water spills out
+		// and fire sets what it lands on alight
+		match spell.element {
+			spells::Element::Earth => {
+				let size = Vector::new(BLOCK_SIZE, BLOCK_SIZE) * spell.area;
+				let block = Object::new(object.pos.x, object.pos.y, Shape::Aabb(size));
+				self.add_ground(block, materials::Material::Stone);
+			}
+			spells::Element::Acid | spells::Element::Void => {
+				let half = CRATER_SIZE * spell.area * 0.5;
+				self.carve(object.pos - half, object.pos + half);
+			}
+			spells::Element::Water => {
+				self.fluid.add(object.pos, WATER_PER_AREA * spell.area);
+			}
+			spells::Element::Fire => {
+				let half = FIRE_REACH * spell.area * 0.5;
+				self.ignite_area(object.pos - half, object.pos + half);
+			}
+			_ => {}
+		}
+	}
+
+	// removes a spell without any of its effects, as if it had never landed
+	fn cancel_spell(&mut self, id: Id) {
+		let spell = self.spells.remove(&id).unwrap();
+		let object = self.objects.remove(&id).unwrap();
+		self.colors.remove(&id);
+		self.sprites.remove(&id);
+		self.emitters.remove(&id);
+		self.lights.remove(&id);
+		let (preset, count) = particles::impact(spell.element);
+		self.particles.emit(object.pos, Vector::new(0.0, 0.0), preset, count / 2);
+	}
+
+	// looks up every spell touching another spell or standing in water in the reaction table,
+	// and carries out what it says
+	fn react(&mut self) {
+		let mut ids: Vec<Id> = self.spells.keys().copied().collect();
+		ids.sort();
+		let mut contacts = vec![];
+		for (i, a) in ids.iter().enumerate() {
+			let (a_min, a_max) = self.objects.get(a).unwrap().bounds();
+			for b in &ids[i + 1..] {
+				let (b_min, b_max) = self.objects.get(b).unwrap().bounds();
+				if object::aabb_aabb(
+					a_min - SOLIDS_MARGIN,
+					a_max + SOLIDS_MARGIN,
+					b_min - SOLIDS_MARGIN,
+					b_max + SOLIDS_MARGIN,
+				) {
+					contacts.push((Reactant::Spell(*a), Reactant::Spell(*b)));
+				}
+			}
+			if self.fluid.depth(self.objects.get(a).unwrap().pos) > WET {
+				contacts.push((Reactant::Spell(*a), Reactant::Water));
+			}
+		}
+
+		for (a, b) in contacts {
+			let element = |reactant| match reactant {
+				Reactant::Spell(id) => self.spells.get(&id).map(|spell| spell.element),
+				Reactant::Water => Some(spells::Element::Water),
+			};
+			let (outcome, swapped) = match (element(a), element(b)) {
+				(Some(ea), Some(eb)) => match self.reactions.find(ea, eb) {
+					Some(reaction) => reaction,
+					None => continue,
 				},
-				winit::event::ElementState::Released => ButtonState::NotHeld,
-			},
-		);
+				// one of them already reacted with something else
+				_ => continue,
+			};
+			let (a, b) = if swapped { (b, a) } else { (a, b) };
+			let pos = match (a, b) {
+				(Reactant::Spell(id), _) | (_, Reactant::Spell(id)) => {
+					self.objects.get(&id).unwrap().pos
+				}
+				_ => continue,
+			};
+			match outcome {
+				reactions::Outcome::Steam => {
+					let (preset, count) = particles::steam();
+					self.particles.emit(pos, Vector::new(0.0, 0.0), preset, count);
+					self.consume(a, pos);
+					self.consume(b, pos);
+				}
+				reactions::Outcome::Electrify(time) => {
+					let duration = match a {
+						Reactant::Spell(id) => self.spells.get(&id).unwrap().duration,
+						Reactant::Water => 1.0,
+					};
+					self.consume(a, pos);
+					if let Reactant::Spell(id) = b {
+						self.end_spell(id);
+					}
+					self.fluid.electrify(pos, time * duration);
+				}
+				reactions::Outcome::Spread(factor) => {
+					if let Reactant::Spell(id) = a {
+						let spell = self.spells.get_mut(&id).unwrap();
+						spell.area *= factor;
+						spell.light *= factor;
+						if let Some(light) = self.lights.get_mut(&id) {
+							light.radius *= factor;
+						}
+					}
+					self.consume(b, pos);
+				}
+				reactions::Outcome::Dissolve => self.consume(b, pos),
+				reactions::Outcome::Cancel => {
+					self.consume(a, pos);
+					self.consume(b, pos);
+				}
+			}
+		}
+	}
+
+	// uses up one side of a reaction
+	fn consume(&mut self, reactant: Reactant, pos: Vector) {
+		match reactant {
+			Reactant::Spell(id) => self.cancel_spell(id),
+			Reactant::Water => self.fluid.remove(pos, REACTED_WATER),
+		}
+	}
+
+	// the spell the queued elements make
+	fn spell(&self) -> Result<spells::Spell, spells::SpellError> {
+		self.recipes.cast(&self.elements, self.config.max_modifiers)
+	}
+
+	// launches the queued elements' spell from the player if there's the mana for it
+	fn cast(&mut self) -> Result<Id, String> {
+		self.spell().map_err(|e| e.to_string())?;
+		let player_pos = self.objects.get(&self.player_id).unwrap().pos;
+		let (object, stats) = self.aim(player_pos).ok_or("nowhere to aim")?;
+		if stats.cost > self.mana {
+			return Err(format!("needs {:.1} mana", stats.cost));
+		}
+		self.mana -= stats.cost;
+		if let Some(name) = &stats.name {
+			if !self.discovered.contains(name) {
+				self.discovered.push(name.clone());
+			}
+		}
+		let id = self.total_ids;
+		self.spawn_spell(object, stats);
+		self.elements.clear();
+		Ok(id)
+	}
+
+	// the spell that casting from origin towards the mouse would launch right now;
+	// stationary spells are placed at the mouse instead, as far as their range allows
+	fn aim(&self, origin: Vector) -> Option<(Object, spells::Spell)> {
+		let (dir, dist) = if self.right_stick.length() > STICK_DEADZONE
+			&& !self.is_action_held(Action::ElementMenu)
+		{
+			(self.right_stick.normalized()?, f32::INFINITY)
+		} else {
+			((self.mouse - origin).normalized()?, (self.mouse - origin).length())
+		};
+		let stats = self.spell().ok()?;
+		let object = if stats.is_stationary() {
+			Object {
+				pos: origin + dir * dist.min(stats.range * self.config.base_spell_range),
+				vel: Vector::new(0.0, 0.0),
+				shape: Shape::Aabb(Vector::new(0.1, 0.3) * stats.area),
+			}
+		} else {
+			Object {
+				pos: origin,
+				vel: dir * self.config.base_spell_speed * stats.speed,
+				shape: Shape::Aabb(Vector::new(0.1, 0.1)),
+			}
+		};
+		Some((object, stats))
+	}
+
+	// the path the pending spell would take from the player, up to where it would land
+	fn trajectory(&self) -> Vec<Vector> {
+		let player_pos = self.objects.get(&self.player_id).unwrap().pos;
+		let (mut object, mut stats) = match self.aim(player_pos) {
+			Some(spell) => spell,
+			None => return vec![],
+		};
+		if stats.is_stationary() {
+			return vec![];
+		}
+		// the spell is done once it's gone its range, so one box around where it starts
+		// holds everything it can run into, give or take its last step
+		let top_speed =
+			object.vel.length() + (self.config.gravity * stats.gravity).abs() * TRAJECTORY_TIME;
+		let reach = stats.range * self.config.base_spell_range + top_speed * TRAJECTORY_STEP;
+		let (min, max) = object.bounds();
+		let solids =
+			self.solids_within(min - reach - SOLIDS_MARGIN, max + reach + SOLIDS_MARGIN);
+		let (entities, targets) = (self.entities(), self.targets());
+		let mut points = vec![object.pos];
+		for _ in 0..(TRAJECTORY_TIME / TRAJECTORY_STEP) as usize {
+			let hit = step_spell(
+				&mut object,
+				&mut stats,
+				&solids,
+				&entities,
+				&targets,
+				&self.config,
+				TRAJECTORY_STEP,
+			);
+			points.push(object.pos);
+			if hit {
+				break;
+			}
+		}
+		points
+	}
+
+	fn update_pad(&mut self, event: gamepad::Event) {
+		use gamepad::{Event, PadAxis};
+		use winit::event::ElementState::*;
+		match event {
+			Event::Pressed(button) => self.update_button(Button::Pad(button), Pressed),
+			Event::Released(button) => self.update_button(Button::Pad(button), Released),
+			Event::Axis(PadAxis::LeftStickX, value) => self.left_stick.x = value,
+			Event::Axis(PadAxis::LeftStickY, value) => self.left_stick.y = value,
+			Event::Axis(PadAxis::RightStickX, value) => self.right_stick.x = value,
+			Event::Axis(PadAxis::RightStickY, value) => self.right_stick.y = value,
+		}
+	}
+
+	// runs one fixed timestep of whichever of the game or the editor is active
+	fn tick(&mut self) {
+		self.previous = self.objects.iter().map(|(id, object)| (*id, object.pos)).collect();
+		match self.editor.take() {
+			Some(mut editor) => {
+				editor.update(self);
+				self.editor = Some(editor);
+			}
+			None => self.update(),
+		}
+	}
+
+	// where to draw an object, alpha of the way from the previous tick to the latest
+	fn interpolated(&self, id: Id, alpha: f32) -> Vector {
+		let pos = self.objects.get(&id).unwrap().pos;
+		match self.previous.get(&id) {
+			Some(previous) => *previous + (pos - *previous) * alpha,
+			None => pos,
+		}
+	}
+
+	// moves an object by a step, stopping it at whatever solids are in the way
+	fn shove(&mut self, id: Id, step: Vector) {
+		let mut object = self.objects.get(&id).unwrap().clone();
+		let vel = std::mem::replace(&mut object.vel, step);
+		let solids = self.solids(&object, 1.0);
+		object.move_and_collide(&solids, 1.0);
+		object.vel = vel;
+		self.objects.insert(id, object);
+	}
+
+	// moves each platform along its way, taking the player with it if they're standing on it
+	// and pushing whatever it runs into out of its way
+	fn move_platforms(&mut self, delta_time: f32) {
+		let [_, (feet, _)] = self.probes();
+		let mut ids: Vec<Id> = self.platforms.keys().copied().collect();
+		ids.sort();
+		let riding =
+			ids.iter().copied().find(|id| object::collide(&feet, self.objects.get(id).unwrap()));
+		// spells run into platforms by themselves
+		let mut bodies: Vec<Id> = self
+			.objects
+			.keys()
+			.copied()
+			.filter(|id| {
+				!self.ground_ids.contains(id)
+					&& !self.triggers.contains_key(id)
+					&& !self.spells.contains_key(id)
+			})
+			.collect();
+		bodies.sort();
+		for id in ids {
+			let platform = self.platforms.get_mut(&id).unwrap();
+			platform.time += delta_time;
+			let pos = platform.pos();
+			let object = self.objects.get_mut(&id).unwrap();
+			let step = pos - object.pos;
+			let (old_min, old_max) = object.bounds();
+			self.terrain.remove(id, old_min, old_max);
+			object.pos = pos;
+			object.vel = step * (1.0 / delta_time);
+			let (min, max) = object.bounds();
+			// the rider and anything pushed are moved while the platform is out of the grid,
+			// so that they only collide with everything else, like a wall they're pushed into
+			if riding == Some(id) {
+				self.shove(self.player_id, step);
+			}
+			let swept_min = Vector::new(old_min.x.min(min.x), old_min.y.min(min.y));
+			let swept_max = Vector::new(old_max.x.max(max.x), old_max.y.max(max.y));
+			for &body in &bodies {
+				let (body_min, body_max) = self.objects.get(&body).unwrap().bounds();
+				let carried = riding == Some(id) && body == self.player_id;
+				if carried
+					|| step.length() == 0.0
+					|| !object::aabb_aabb(swept_min, swept_max, body_min, body_max)
+				{
+					continue;
+				}
+				// out past the platform's leading side
+				let push = if step.x.abs() >= step.y.abs() {
+					match step.x > 0.0 {
+						true => Vector::new(max.x - body_min.x, 0.0),
+						false => Vector::new(min.x - body_max.x, 0.0),
+					}
+				} else {
+					match step.y > 0.0 {
+						true => Vector::new(0.0, max.y - body_min.y),
+						false => Vector::new(0.0, min.y - body_max.y),
+					}
+				};
+				self.shove(body, push);
+			}
+			self.terrain.insert(id, min, max);
+		}
 	}
 
 	fn update(&mut self) {
-		let delta_time = 1.0 / TICKRATE;
-		use winit::event::VirtualKeyCode::*;
+		let delta_time = 1.0 / self.config.tickrate;
+		self.snapshot = self.input.sample();
+		self.move_platforms(delta_time);
 
-		let vx = MOVE
-			* if self.is_button_held(Button::Key(A)) {
+		let vx = self.config.move_speed
+			* if self.is_action_held(Action::MoveLeft) {
 				-1.0
-			} else if self.is_button_held(Button::Key(D)) {
+			} else if self.is_action_held(Action::MoveRight) {
 				1.0
+			} else if self.left_stick.x.abs() > STICK_DEADZONE {
+				self.left_stick.x
 			} else {
 				0.0
 			};
 
-		let Object { pos: player_pos, vel: player_vel, .. } =
-			self.objects.get(&self.player_id).unwrap();
-		let player_pos = player_pos.clone();
-		let player_vel = player_vel.clone();
-		let ground_objects: Vec<Object> =
-			self.ground_ids.iter().map(|id| self.objects.get(id).unwrap().clone()).collect();
-
-		let on_ceiling = ground_objects.iter().any(|g| {
-			object::collide(
-				&Object::new(
-					player_pos.x,
-					player_pos.y,
-					Shape::Line(Vector::new(0.0, PLAYER_HEIGHT / 2.0 + GROUND_CHECK)),
-				),
-				g,
-			)
-		});
-		let on_ground = ground_objects.iter().any(|g| {
-			object::collide(
-				&Object::new(
-					player_pos.x,
-					player_pos.y,
-					Shape::Line(Vector::new(0.0, -(PLAYER_HEIGHT / 2.0 + GROUND_CHECK))),
-				),
-				g,
-			)
-		});
-
-		*self.colors.get_mut(&self.player_id).unwrap() = if on_ground {
-			[0.0, 1.0, 0.0]
-		} else if on_ceiling {
-			[1.0, 0.0, 0.0]
+		let player = self.objects.get(&self.player_id).unwrap();
+		let (player_pos, player_vel) = (player.pos, player.vel);
+		let submerged = self.fluid.depth(player_pos);
+		// shocked water stuns whoever is standing in it
+		let vx = if self.fluid.charge(player_pos) > 0.0 && submerged > WET {
+			0.0
+		} else if submerged > WET {
+			vx * WATER_SLOW
 		} else {
-			[0.0, 0.0, 1.0]
+			vx
 		};
+		let [(_, on_ceiling), (_, on_ground)] = self.probes();
 
-		let vy = if self.is_button_held(Button::Key(Space)) && on_ground {
-			JUMP
+		let player_sprite = self.sprites.get_mut(&self.player_id).unwrap();
+		player_sprite.play(if !on_ground {
+			"player_air"
+		} else if vx != 0.0 {
+			"player_walk"
+		} else {
+			"player_idle"
+		});
+		if vx != 0.0 {
+			player_sprite.flipped = vx < 0.0;
+		}
+		for sprite in self.sprites.values_mut() {
+			sprite.time += delta_time;
+		}
+
+		let vy = if self.is_action_held(Action::Jump) && on_ground {
+			self.config.jump
 		} else if on_ground {
 			0.0
 		} else if on_ceiling {
-			CEILING_BOUNCE
+			self.config.ceiling_bounce
+		} else if submerged > WET && self.is_action_held(Action::Jump) {
+			SWIM
+		} else if submerged > WET {
+			(player_vel.y + (BUOYANCY * submerged - self.config.gravity) * delta_time)
+				* WATER_DRAG
 		} else {
-			player_vel.y - GRAVITY * delta_time
+			player_vel.y - self.config.gravity * delta_time
 		};
 
 		self.objects.get_mut(&self.player_id).unwrap().vel = Vector::new(vx, vy);
+		let ground_objects = self.solids(self.objects.get(&self.player_id).unwrap(), delta_time);
 		self.objects
 			.get_mut(&self.player_id)
 			.unwrap()
 			.move_and_collide(&ground_objects, delta_time);
 
-		for key in [Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0] {
-			if self.is_button_pressed(Button::Key(key)) {
-				let element = match key {
-					Key1 => spells::Element::Earth,
-					Key2 => spells::Element::Water,
-					Key3 => spells::Element::Air,
-					Key4 => spells::Element::Fire,
-					Key5 => spells::Element::Acid,
-					Key6 => spells::Element::Pressure,
-					Key7 => spells::Element::Shock,
-					Key8 => spells::Element::Radiance,
-					Key9 => spells::Element::Life,
-					Key0 => spells::Element::Void,
-					_ => unreachable!(),
-				};
+		for element in spells::Element::ALL {
+			for _ in 0..self.action_presses(Action::QueueElement(element)) {
+				self.elements.push(element);
+			}
+		}
+		// point the stick at an element and let it spring back to queue it
+		if self.is_action_held(Action::ElementMenu) {
+			if self.right_stick.length() > 0.5 {
+				self.menu_choice = Some(hud::radial_choice(self.right_stick));
+			} else if let Some(element) = self.menu_choice.take() {
 				self.elements.push(element);
 			}
+		} else {
+			// letting go of the menu while pointing at an element queues it too
+			if let (Some(element), true) =
+				(self.menu_choice, self.action_releases(Action::ElementMenu) > 0)
+			{
+				self.elements.push(element);
+			}
+			self.menu_choice = None;
 		}
 
-		if self.is_button_pressed(Button::Mouse(MouseButton::Left)) {
-			if let Some(dir) = (self.mouse - player_pos).normalized() {
-				if !self.elements.is_empty() {
-					let spell_id = self.total_ids;
-					self.total_ids += 1;
-					let spell_stats = spells::Spell::new(&self.elements);
-					let spell_object = Object {
-						pos: player_pos,
-						vel: dir * BASE_SPELL_SPEED * spell_stats.speed,
-						shape: Shape::Aabb(Vector::new(0.1, 0.1)),
-					};
-					self.objects.insert(spell_id, spell_object);
-					self.spells.insert(spell_id, spell_stats);
-					self.elements.clear();
-				}
+		if self.action_presses(Action::Spellbook) % 2 == 1 {
+			self.show_spellbook = !self.show_spellbook;
+		}
+
+		if self.action_presses(Action::Cast) > 0 {
+			if let Err(e) = self.cast() {
+				eprintln!("can't cast: {}", e);
 			}
 		}
 
+		self.mana = (self.mana + self.config.mana_regen * delta_time).min(self.config.max_mana);
+
 		let mut hits = vec![];
-		for (id, stats) in self.spells.iter_mut() {
-			let objects: Vec<Object> = self
-				.objects
-				.iter()
-				.filter(|t| *t.0 != self.player_id && t.0 != id)
-				.map(|t| t.1.clone())
-				.collect();
-			let spell = self.objects.get_mut(&id).unwrap();
-			let start_pos = spell.pos.clone();
-			if spell.move_and_collide(&objects, delta_time)
-				|| stats.dist_traveled > stats.range * BASE_SPELL_RANGE
+		let (entities, targets) = (self.entities(), self.targets());
+		let mut spell_ids: Vec<Id> = self.spells.keys().copied().collect();
+		spell_ids.sort();
+		for id in spell_ids {
+			let solids = self.solids(self.objects.get(&id).unwrap(), delta_time);
+			let object = self.objects.get_mut(&id).unwrap();
+			let stats = self.spells.get_mut(&id).unwrap();
+			if step_spell(object, stats, &solids, &entities, &targets, &self.config, delta_time)
 			{
-				hits.push(*id);
-			} else {
-				stats.dist_traveled += (spell.pos - start_pos).length();
-				spell.vel.y -= GRAVITY * delta_time;
+				hits.push(id);
 			}
 		}
 		for id in hits {
-			self.spells.remove(&id);
-			self.objects.remove(&id);
-			// let spell = self.spells.get(&id).unwrap();
-			// match spell.element {
-			// 	_ => todo!(),
-			// }
+			// the script of something an earlier spell hit may have removed this one
+			if !self.spells.contains_key(&id) {
+				continue;
+			}
+			self.end_spell(id);
+		}
+		self.react();
+		self.burn(delta_time);
+		self.sense(delta_time);
+
+		let player_bounds = self.objects.get(&self.player_id).unwrap().bounds();
+		let margin = self.config.ground_check;
+		for id in self.script_ids() {
+			// an earlier script may have removed this object
+			let Some(instance) = self.scripts.get_mut(&id) else { continue };
+			instance.age += delta_time;
+			self.run_script(id, "tick", &[("dt", script::Value::Number(delta_time))]);
+			// the player standing on or walking into a scripted object
+			if let Some(object) = self.objects.get(&id) {
+				let (min, max) = object.bounds();
+				if object::aabb_aabb(
+					min - margin,
+					max + margin,
+					player_bounds.0,
+					player_bounds.1,
+				) {
+					self.run_script(id, "touch", &[]);
+				}
+			}
+		}
+
+		let (terrain, objects) = (&self.terrain, &self.objects);
+		let walls: Vec<(Vector, Vector)> = self
+			.spells
+			.iter()
+			.filter(|(_, stats)| stats.is_stationary())
+			.map(|(id, _)| objects.get(id).unwrap().bounds())
+			.collect();
+		self.fluid.step(delta_time, |min, max| {
+			let blocks = terrain.query(min, max);
+			blocks
+				.iter()
+				.map(|id| objects.get(id).unwrap().bounds())
+				.chain(walls.iter().copied())
+				.any(|(block_min, block_max)| object::aabb_aabb(min, max, block_min, block_max))
+		});
+
+		let mut expired = vec![];
+		for (id, emitter) in self.emitters.iter_mut() {
+			let alive = match self.objects.get(id) {
+				Some(object) => {
+					self.particles.run_emitter(emitter, object.pos, object.vel, delta_time)
+				}
+				None => false,
+			};
+			if !alive {
+				expired.push(*id);
+			}
+		}
+		for id in expired {
+			self.emitters.remove(&id);
+		}
+		self.particles.step(delta_time);
+
+		let mut expired = vec![];
+		for (id, light) in self.lights.iter_mut() {
+			match self.objects.get(id) {
+				Some(object) => light.pos = object.pos,
+				None => light.lifetime -= delta_time,
+			}
+			if light.lifetime <= 0.0 {
+				expired.push(*id);
+			}
+		}
+		for id in expired {
+			self.lights.remove(&id);
+		}
+	}
+}
+
+// moves a spell for one tick, returning whether it's done: it hit something it can't
+// bounce off or pass through, ran out of range, or stood for its whole duration
+fn step_spell(
+	spell: &mut Object,
+	stats: &mut spells::Spell,
+	solids: &[Object],
+	entities: &[(Id, Object)],
+	targets: &[Vector],
+	config: &config::Config,
+	delta_time: f32,
+) -> bool {
+	stats.age += delta_time;
+	if stats.is_stationary() {
+		return stats.age > stats.duration * STATIONARY_TIME;
+	}
+
+	// turn towards the nearest target without changing speed
+	let nearest = targets
+		.iter()
+		.min_by(|a, b| (**a - spell.pos).length().total_cmp(&(**b - spell.pos).length()));
+	if let (Some(rate), Some(target)) = (stats.homing(), nearest) {
+		let heading = spell.vel.y.atan2(spell.vel.x);
+		let wanted = (target.y - spell.pos.y).atan2(target.x - spell.pos.x);
+		let turn = (wanted - heading + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU)
+			- std::f32::consts::PI;
+		let heading = heading + turn.clamp(-rate * delta_time, rate * delta_time);
+		spell.vel = Vector::new(heading.cos(), heading.sin()) * spell.vel.length();
+	}
+
+	let start_pos = spell.pos;
+	let moved_to = spell.pos + spell.vel * delta_time;
+	if spell.move_and_collide(solids, delta_time) {
+		if !stats.bounce() {
+			return true;
+		}
+		// being pushed out sideways means it hit a wall, otherwise a floor or ceiling
+		let push = spell.pos - moved_to;
+		if push.x.abs() > push.y.abs() {
+			spell.vel.x *= -1.0;
+		} else {
+			spell.vel.y *= -1.0;
+		}
+	}
+	for (id, entity) in entities {
+		if object::collide(spell, entity) && !stats.pierced.contains(id) {
+			stats.pierced.push(*id);
+			if !stats.pierce() {
+				return true;
+			}
 		}
 	}
+	if stats.dist_traveled > stats.range * config.base_spell_range {
+		return true;
+	}
+	stats.dist_traveled += (spell.pos - start_pos).length();
+	spell.vel.y -= config.gravity * stats.gravity * delta_time;
+	false
 }
 
-async fn run() {
+// appends the quad for an object, textured with its sprite if it has one
+fn push_quad(
+	world: &World,
+	atlas: &sprite::Atlas,
+	id: Id,
+	alpha: f32,
+	flat: &mut Vec<Vertex<render::Color>>,
+	textured: &mut Vec<Vertex<Textured>>,
+) {
+	let mut color = *world.colors.get(&id).unwrap();
+	if world.burning.contains_key(&id) {
+		color = [0, 1, 2].map(|i| color[i] * BURNING_TINT[i]);
+	}
+	let object = world.objects.get(&id).unwrap();
+	let (min, max) = match object.shape {
+		Shape::Aabb(_) => object.bounds(),
+		Shape::Line(_dir) => todo!(),
+	};
+	let offset = world.interpolated(id, alpha) - object.pos;
+	let (min, max) = (min + offset, max + offset);
+	match world.sprites.get(&id).and_then(|sprite| atlas.uv(sprite)) {
+		Some((uv_min, uv_max)) => {
+			let vertex = |x: f32, y: f32, u: f32, v: f32| {
+				Vertex::new(x, y, Textured { uv: Vector::new(u, v), color })
+			};
+			textured.extend([
+				vertex(min.x, min.y, uv_min.x, uv_max.y),
+				vertex(max.x, min.y, uv_max.x, uv_max.y),
+				vertex(min.x, max.y, uv_min.x, uv_min.y),
+				vertex(max.x, max.y, uv_max.x, uv_min.y),
+				vertex(min.x, max.y, uv_min.x, uv_min.y),
+				vertex(max.x, min.y, uv_max.x, uv_max.y),
+			]);
+		}
+		None => flat.extend([
+			Vertex::new(min.x, min.y, color),
+			Vertex::new(max.x, min.y, color),
+			Vertex::new(min.x, max.y, color),
+			Vertex::new(max.x, max.y, color),
+			Vertex::new(min.x, max.y, color),
+			Vertex::new(max.x, min.y, color),
+		]),
+	}
+}
+
+// `--config path` picks another config file, other `--setting value` flags override it;
+// returns the config file's path along with the world
+fn load_world(args: &[String]) -> (String, World) {
+	let config_path = config::path(args, "config").unwrap_or(CONFIG_PATH).to_string();
+	let config = config::Config::load_with_args(&config_path, args).unwrap_or_else(|e| {
+		eprintln!("{}", e);
+		config::Config::default()
+	});
+	let mut world = World::new(config);
+	match level::Level::load(&world.level_path.clone()) {
+		Ok(level) => world.load_level(&level),
+		// falls back to an empty world, which picks the level up once the file is fixed
+		Err(e) => eprintln!("{}", e),
+	}
+	(config_path, world)
+}
+
+async fn run(args: Vec<String>) {
 	env_logger::init();
 
 	let event_loop = winit::event_loop::EventLoop::new();
 	let window = winit::window::WindowBuilder::new().build(&event_loop).unwrap();
 	let mut state = render::State::new(&window).await;
+	let atlas = sprite::Atlas::load("assets/atlas.png", "assets/atlas.txt").unwrap();
+	let atlas_texture = state.create_texture(atlas.width, atlas.height, &atlas.pixels);
+	let mut lightmap = lighting::Lightmap::new(&state);
+	let mut gamepads = gamepad::backend();
+	let font = sprite::Atlas::load("assets/font.png", "assets/font.txt").unwrap();
+	let font_texture = state.create_texture(font.width, font.height, &font.pixels);
 	let mut then = std::time::Instant::now();
 	let mut leftover_time = 0.0;
 
-	let mut world = World {
-		player_id: 0,
-		ground_ids: vec![1, 2, 3, 4, 5, 6, 7],
-		total_ids: 8,
-
-		objects: HashMap::from([
-			(
-				0,
-				Object::new(
-					0.0,
-					0.5,
-					Shape::Aabb(Vector::new(PLAYER_HEIGHT / 2.0, PLAYER_HEIGHT)),
-				),
-			),
-			(1, Object::new(0.0, 1.5, Shape::Aabb(Vector::new(3.0, 1.0)))),
-			(2, Object::new(0.0, -1.5, Shape::Aabb(Vector::new(3.0, 1.0)))),
-			(3, Object::new(1.5, 0.0, Shape::Aabb(Vector::new(1.0, 3.0)))),
-			(4, Object::new(-1.5, 0.0, Shape::Aabb(Vector::new(1.0, 3.0)))),
-			(5, Object::new(0.5, 0.2, Shape::Aabb(Vector::new(0.4, 0.1)))),
-			(6, Object::new(0.0, -0.2, Shape::Aabb(Vector::new(0.4, 0.1)))),
-			(7, Object::new(-0.5, -0.6, Shape::Aabb(Vector::new(0.4, 0.1)))),
-		]),
-		colors: HashMap::from([
-			(0, [0.0, 0.0, 1.0]),
-			(1, [0.0; 3]),
-			(2, [0.0; 3]),
-			(3, [0.0; 3]),
-			(4, [0.0; 3]),
-			(5, [0.0; 3]),
-			(6, [0.0; 3]),
-			(7, [0.0; 3]),
-		]),
-		spells: HashMap::new(),
-
-		elements: vec![],
-		buttons: HashMap::new(),
-		mouse: Vector::new(0.0, 0.0),
-	};
+	let (config_path, mut world) = load_world(&args);
+	// each still block's quad, kept between frames and only redone when the block changes
+	let mut terrain = BTreeMap::new();
+	let mut watcher = watch::Watcher::new(
+		&[config_path.as_str(), RECIPES_PATH, REACTIONS_PATH]
+			.iter()
+			.map(|path| path.to_string())
+			.chain(state.shaders.paths())
+			.collect::<Vec<String>>(),
+	);
+	let mut reload_errors = std::collections::BTreeMap::new(); // by path, shown until fixed
+	let mut stats = debug::Stats::default();
 
 	event_loop.run(move |event, _, control_flow| {
 		use winit::{event::Event, event::WindowEvent, event_loop::ControlFlow};
 		*control_flow = ControlFlow::Poll;
 		match event {
 			Event::WindowEvent { event, window_id } if window_id == window.id() => match event {
-				WindowEvent::KeyboardInput { input, .. } => match input.virtual_keycode {
-					Some(key) => world.update_button(Button::Key(key), input.state),
-					None => {}
-				},
+				WindowEvent::KeyboardInput { input, .. } => {
+					if let Some(key) = input.virtual_keycode {
+						world.update_button(Button::Key(key), input.state)
+					}
+					world.update_button(Button::Scancode(input.scancode), input.state)
+				}
+				WindowEvent::ReceivedCharacter(c) => {
+					if let Some(console) = &mut world.console {
+						console.type_char(c);
+					}
+				}
 				WindowEvent::MouseInput { button, state, .. } => {
 					world.update_button(Button::Mouse(button), state)
 				}
@@ -305,12 +1571,72 @@ async fn run() {
 				then = now;
 
 				let delta_time = delta_time.as_micros() as f32 / 1000.0 / 1000.0;
-				let updates_time = delta_time + leftover_time;
-				let updates_to_run = (updates_time * TICKRATE).floor();
-				leftover_time = updates_time - updates_to_run / TICKRATE;
+				let updates_time = match world.paused {
+					true => 0.0,
+					false => delta_time * world.time_scale + leftover_time,
+				};
+				let updates_to_run = (updates_time * world.config.tickrate).floor();
+				for event in gamepads.poll() {
+					world.update_pad(event);
+				}
+				// whichever level is being played, and the scripts in it
+				watcher.watch(&world.level_path);
+				for instance in world.scripts.values() {
+					watcher.watch(&instance.program.path);
+				}
+				for path in watcher.poll() {
+					let result = match path.as_str() {
+						path if path == config_path => {
+							config::Config::load_with_args(path, &args)
+								.map(|config| world.set_config(config))
+						}
+						path if path == world.level_path => {
+							level::Level::load(path).map(|level| world.reload_level(&level))
+						}
+						RECIPES_PATH => {
+							recipes::Recipes::load(&path).map(|recipes| world.recipes = recipes)
+						}
+						REACTIONS_PATH => reactions::Reactions::load(&path)
+							.map(|reactions| world.reactions = reactions),
+						path if world.scripts.values().any(|i| i.program.path == path) => {
+							world.reload_script(path)
+						}
+						path if state.shaders.paths().iter().any(|shader| shader == path) => {
+							state.shaders.reload(path)
+						}
+						// from a level that's been left behind
+						_ => continue,
+					};
+					match result {
+						Ok(()) => {
+							eprintln!("reloaded {}", path);
+							reload_errors.remove(&path);
+						}
+						Err(e) => {
+							eprintln!("{}", e);
+							reload_errors.insert(path, e);
+						}
+					}
+				}
+				leftover_time = updates_time - updates_to_run / world.config.tickrate;
+				// after a long stall, such as while the window is dragged, the time that can't
+				// be caught up on is dropped rather than piling up into ever longer frames
+				let updates_to_run = if updates_to_run > MAX_CATCH_UP {
+					leftover_time = 0.0;
+					MAX_CATCH_UP
+				} else {
+					updates_to_run
+				} + std::mem::take(&mut world.steps) as f32;
+				stats.record(delta_time, updates_to_run);
 				for _ in 0..updates_to_run as usize {
-					world.update();
+					world.tick();
 				}
+				// how far between the last two ticks the frame falls; paused frames show the
+				// latest tick so that stepping shows where things have got to
+				let alpha = match world.paused {
+					true => 1.0,
+					false => (leftover_time * world.config.tickrate).min(1.0),
+				};
 
 				match state.surface.get_current_texture() {
 					Err(e) => {
@@ -323,66 +1649,251 @@ async fn run() {
 						let mut encoder = state.device.create_command_encoder(
 							&wgpu::CommandEncoderDescriptor { label: None },
 						);
-						encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-							label: Some("Clear Screen"),
-							color_attachments: &[wgpu::RenderPassColorAttachment {
-								view: &view,
-								resolve_target: None,
-								ops: wgpu::Operations {
-									load: wgpu::LoadOp::Clear(wgpu::Color {
-										r: 1.0,
-										g: 0.0,
-										b: 1.0,
-										a: 1.0,
-									}),
-									store: true,
+						state.clear(&mut encoder, &view, [1.0, 0.0, 1.0]);
+
+						// terrain only changes when spells hit it, so its quads are kept between frames
+						for id in std::mem::take(&mut world.terrain_changed) {
+							// moving platforms are drawn with everything else that moves
+							if !world.ground_ids.contains(&id)
+								|| world.platforms.contains_key(&id)
+							{
+								terrain.remove(&id);
+								continue;
+							}
+							let (mut flat, mut textured) = (vec![], vec![]);
+							push_quad(&world, &atlas, id, 1.0, &mut flat, &mut textured);
+							terrain.insert(id, (flat, textured));
+						}
+						let mut ids: Vec<&Id> = world
+							.colors
+							.keys()
+							.filter(|id| {
+								!world.ground_ids.contains(id)
+									|| world.platforms.contains_key(id)
+							})
+							.collect();
+						ids.sort();
+						let mut flat: Vec<Vertex<render::Color>> =
+							terrain.values().flat_map(|(flat, _)| flat.clone()).collect();
+						let mut textured: Vec<Vertex<Textured>> = terrain
+							.values()
+							.flat_map(|(_, textured)| textured.clone())
+							.collect();
+						for id in ids {
+							push_quad(&world, &atlas, *id, alpha, &mut flat, &mut textured);
+						}
+						// water fills its cells from the bottom and turns yellow while shocked
+						for (key, cell) in world.fluid.cells() {
+							let (min, max) = fluid::bounds(key);
+							let max =
+								Vector::new(max.x, min.y + fluid::CELL * cell.amount.min(1.0));
+							let t = cell.charge.min(1.0);
+							let color = [0.2 + 0.7 * t, 0.4 + 0.5 * t, 0.9 - 0.6 * t];
+							flat.extend([
+								Vertex::new(min.x, min.y, color),
+								Vertex::new(max.x, min.y, color),
+								Vertex::new(min.x, max.y, color),
+								Vertex::new(max.x, max.y, color),
+								Vertex::new(min.x, max.y, color),
+								Vertex::new(max.x, min.y, color),
+							]);
+						}
+						state.render(
+							&mut encoder,
+							&view,
+							&state.shaders.get("flat.wgsl"),
+							&wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x3],
+							flat,
+							wgpu::BlendState::REPLACE,
+						);
+						state.render_textured(
+							&mut encoder,
+							&view,
+							&state.shaders.get("textured.wgsl"),
+							&wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x3],
+							textured,
+							&atlas_texture,
+							wgpu::BlendState::ALPHA_BLENDING,
+						);
+						// ground that gives off light, like a burning block, would otherwise
+						// hide its own light inside itself
+						let occluders: Vec<(Vector, Vector)> = world
+							.ground_ids
+							.iter()
+							.filter(|id| !world.lights.contains_key(id))
+							.map(|id| world.objects.get(id).unwrap())
+							.filter_map(|object| match object.shape {
+								Shape::Aabb(size) => {
+									Some((object.pos - size * 0.5, object.pos + size * 0.5))
+								}
+								Shape::Line(_) => None,
+							})
+							.collect();
+						// lights attached to objects are drawn where their objects are
+						let lights: Vec<lighting::Light> = world
+							.lights
+							.iter()
+							.map(|(id, light)| match world.objects.contains_key(id) {
+								true => lighting::Light {
+									pos: world.interpolated(*id, alpha),
+									..light.clone()
 								},
-							}],
-							depth_stencil_attachment: None,
-						});
-
-						for (id, color) in
-							world.colors.iter().map(|(id, color)| (id, *color)).chain(
-								world.spells.iter().map(|(id, spell)| {
-									(
-										id,
-										match spell.element {
-											spells::Element::Earth => [0.4, 0.2, 0.0],
-											spells::Element::Void => [0.0, 0.0, 0.0],
-											spells::Element::Life => [1.0, 1.0, 1.0],
-											spells::Element::Water => [0.0, 0.0, 1.0],
-											spells::Element::Air => [0.0, 1.0, 1.0],
-											spells::Element::Fire => [1.0, 0.0, 0.0],
-											spells::Element::Acid => [0.0, 1.0, 0.0],
-											spells::Element::Shock => [1.0, 1.0, 0.0],
-											spells::Element::Pressure => [0.4, 0.6, 1.0],
-											spells::Element::Radiance => [1.0, 0.0, 1.0],
+								false => light.clone(),
+							})
+							.collect();
+						lightmap.render(
+							&state,
+							&mut encoder,
+							&view,
+							world.ambient,
+							&lights.iter().collect::<Vec<_>>(),
+							&occluders,
+						);
+						state.render_instanced(
+							&mut encoder,
+							&view,
+							&state.shaders.get("particle.wgsl"),
+							&wgpu::vertex_attr_array![0 => Float32x2],
+							vec![
+								Vertex::new(-0.5, -0.5, ()),
+								Vertex::new(0.5, -0.5, ()),
+								Vertex::new(-0.5, 0.5, ()),
+								Vertex::new(0.5, 0.5, ()),
+								Vertex::new(-0.5, 0.5, ()),
+								Vertex::new(0.5, -0.5, ()),
+							],
+							&wgpu::vertex_attr_array![1 => Float32x2, 2 => Float32x4, 3 => Float32],
+							world
+								.particles
+								.particles
+								.iter()
+								.map(|p| {
+									Vertex::new(
+										p.pos.x,
+										p.pos.y,
+										particles::Instance {
+											color: p.color(),
+											size: p.preset.size,
 										},
 									)
-								}),
-							) {
-							let object = world.objects.get(id).unwrap();
-							let p = object.pos;
-							let vertices = match object.shape {
-								Shape::Aabb(Vector { x: w, y: h }) => vec![
-									Vertex::new(p.x - w / 2.0, p.y - h / 2.0, color),
-									Vertex::new(p.x + w / 2.0, p.y - h / 2.0, color),
-									Vertex::new(p.x - w / 2.0, p.y + h / 2.0, color),
-									Vertex::new(p.x + w / 2.0, p.y + h / 2.0, color),
-									Vertex::new(p.x - w / 2.0, p.y + h / 2.0, color),
-									Vertex::new(p.x + w / 2.0, p.y - h / 2.0, color),
-								],
-								Shape::Line(_dir) => todo!(),
-							};
-							state.render(
-								&mut encoder,
-								&view,
-								&wgpu::include_wgsl!("flat.wgsl"),
-								&wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x3]
-									.to_vec(),
-								vertices,
+								})
+								.collect(),
+						);
+
+						let trajectory = match world.editor {
+							Some(_) => vec![],
+							None => world.trajectory(),
+						};
+						let mut preview = match &world.editor {
+							Some(editor) => editor.overlay(&world),
+							None => vec![],
+						};
+						for (id, trigger) in &world.triggers {
+							let (min, max) = world.objects.get(id).unwrap().bounds();
+							editor::outline(&mut preview, min, max, trigger.kind.color());
+						}
+						if world.show_debug {
+							preview.extend(debug::overlay(&world));
+						}
+						for pair in trajectory.windows(2) {
+							let (a, b) = (pair[0], pair[1]);
+							if let Some(dir) = (b - a).normalized() {
+								let side = Vector::new(-dir.y, dir.x) * 0.005;
+								let color = [1.0, 1.0, 1.0];
+								preview.extend([
+									Vertex::new((a - side).x, (a - side).y, color),
+									Vertex::new((b - side).x, (b - side).y, color),
+									Vertex::new((a + side).x, (a + side).y, color),
+									Vertex::new((b + side).x, (b + side).y, color),
+									Vertex::new((a + side).x, (a + side).y, color),
+									Vertex::new((b - side).x, (b - side).y, color),
+								]);
+							}
+						}
+						state.render(
+							&mut encoder,
+							&view,
+							&state.shaders.get("flat.wgsl"),
+							&wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x3],
+							preview,
+							wgpu::BlendState::REPLACE,
+						);
+
+						let mut hud = vec![];
+						let extent = state.view_extent();
+						let mut lines = match &world.editor {
+							Some(editor) => editor.help(&world),
+							None => hud::status(
+								&world.elements,
+								&world.spell(),
+								(world.health, world.config.max_health),
+								(world.mana, world.config.max_mana),
+							),
+						};
+						if world.show_spellbook {
+							lines.extend(hud::spellbook(&world.recipes, &world.discovered));
+						}
+						if world.show_debug {
+							lines.extend(debug::panel(&world, &stats));
+							debug::labels(&mut hud, &font, &world);
+						}
+						if world.paused {
+							lines.push(hud::line(
+								"paused (f4 resumes, f5 steps)",
+								[1.0, 1.0, 0.4],
+							));
+						}
+						if world.time_scale != 1.0 {
+							lines.push(hud::line(
+								&format!("speed {}x (f6 slower, f7 faster)", world.time_scale),
+								[1.0, 1.0, 0.4],
+							));
+						}
+						for e in reload_errors.values() {
+							lines.push(hud::line(e, [1.0, 0.3, 0.3]));
+						}
+						if let Some(index) = world.rebinding {
+							lines.push(hud::line(
+								&format!(
+									"press a button for {} (escape skips)",
+									Action::all()[index].name()
+								),
+								[1.0, 1.0, 0.4],
+							));
+						}
+						hud::text(
+							&mut hud,
+							&font,
+							&lines,
+							Vector::new(-extent.x + 0.05, extent.y - 0.05),
+							0.05,
+						);
+						if let Some(console) = &world.console {
+							let lines = console.lines();
+							let height = 0.05;
+							let top = -extent.y
+								+ 0.05 + height + (lines.len() - 1) as f32 * height * 1.25;
+							hud::text(
+								&mut hud,
+								&font,
+								&lines,
+								Vector::new(-extent.x + 0.05, top),
+								height,
 							);
 						}
+						if world.is_action_held(Action::ElementMenu) {
+							let player_pos = world.interpolated(world.player_id, alpha);
+							hud::radial_menu(&mut hud, &font, player_pos, world.menu_choice);
+						}
+						state.render_textured(
+							&mut encoder,
+							&view,
+							&state.shaders.get("textured.wgsl"),
+							&wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x3],
+							hud,
+							&font_texture,
+							wgpu::BlendState::ALPHA_BLENDING,
+						);
 						state.queue.submit(std::iter::once(encoder.finish()));
 						output.present();
 					}
@@ -394,5 +1905,258 @@ async fn run() {
 }
 
 fn main() {
-	pollster::block_on(run());
+	let args = std::env::args().skip(1).collect::<Vec<String>>();
+	// `--script path` runs console commands from a file without opening a window
+	match config::path(&args, "script") {
+		Some(path) => {
+			let (_, mut world) = load_world(&args);
+			if let Err(e) = console::script(&mut world, path) {
+				eprintln!("{}", e);
+				std::process::exit(1);
+			}
+		}
+		None => pollster::block_on(run(args)),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::rc::Rc;
+
+	fn script(source: &str) -> Option<Rc<script::Program>> {
+		Some(Rc::new(script::Program::parse("test", source).unwrap()))
+	}
+
+	fn block(x: f32, y: f32, script: Option<Rc<script::Program>>) -> level::Block {
+		let material = materials::Material::Stone;
+		let color = material.color();
+		let (pos, size) = (Vector::new(x, y), Vector::new(0.2, 0.2));
+		level::Block { pos, size, material, color, motion: None, script }
+	}
+
+	fn world(ground: Vec<level::Block>, triggers: Vec<level::Trigger>) -> World {
+		let mut world = World::new(config::Config::default());
+		let spawn = Vector::new(0.0, 5.0);
+		let ambient = level::AMBIENT;
+		world.load_level(&level::Level { spawn, ambient, ground, targets: vec![], triggers });
+		world
+	}
+
+	fn trigger(kind: triggers::Kind, script: Option<Rc<script::Program>>) -> level::Trigger {
+		let (pos, size) = (Vector::new(0.0, 5.0), Vector::new(2.0, 2.0));
+		level::Trigger { pos, size, kind, script }
+	}
+
+	// feeds events through the fake gamepad backend the way the game loop does
+	fn pad(world: &mut World, events: &[gamepad::Event]) {
+		use gamepad::Backend;
+		let mut fake = gamepad::Fake::default();
+		fake.events.extend(events);
+		for event in fake.poll() {
+			world.update_pad(event);
+		}
+		world.tick();
+	}
+
+	#[test]
+	fn left_stick_walks() {
+		use gamepad::{Event, PadAxis};
+		let mut world = world(vec![], vec![]);
+		pad(&mut world, &[Event::Axis(PadAxis::LeftStickX, STICK_DEADZONE / 2.0)]);
+		assert_eq!(world.objects.get(&world.player_id).unwrap().vel.x, 0.0);
+		let stick =
+			[Event::Axis(PadAxis::LeftStickX, -0.5), Event::Axis(PadAxis::LeftStickY, 0.5)];
+		pad(&mut world, &stick);
+		assert_eq!((world.left_stick.x, world.left_stick.y), (-0.5, 0.5));
+		let player = world.objects.get(&world.player_id).unwrap();
+		assert_eq!(player.vel.x, -0.5 * world.config.move_speed);
+		assert!(player.pos.x < 0.0);
+	}
+
+	#[test]
+	fn right_stick_aims() {
+		use gamepad::{Event, PadAxis};
+		let mut world = world(vec![], vec![]);
+		world.elements = vec![spells::Element::Fire];
+		world.mouse = Vector::new(-1.0, 5.0);
+		let origin = Vector::new(0.0, 5.0);
+		pad(&mut world, &[]);
+		assert!(world.aim(origin).unwrap().0.vel.x < 0.0);
+		pad(&mut world, &[Event::Axis(PadAxis::RightStickY, 1.0)]);
+		let (spell, _) = world.aim(origin).unwrap();
+		assert_eq!((spell.vel.x, spell.vel.y > 0.0), (0.0, true));
+	}
+
+	#[test]
+	fn radial_menu_queues_elements() {
+		use gamepad::{Event, PadAxis, PadButton};
+		let mut world = world(vec![], vec![]);
+		let up = hud::radial_choice(Vector::new(0.0, 1.0));
+		let right = hud::radial_choice(Vector::new(1.0, 0.0));
+		// pointing at an element and letting the stick spring back
+		pad(
+			&mut world,
+			&[Event::Pressed(PadButton::LeftTrigger2), Event::Axis(PadAxis::RightStickY, 1.0)],
+		);
+		assert_eq!((world.menu_choice, world.elements.len()), (Some(up), 0));
+		pad(&mut world, &[Event::Axis(PadAxis::RightStickY, 0.0)]);
+		assert_eq!((world.menu_choice, world.elements.clone()), (None, vec![up]));
+		// letting go of the menu while still pointing
+		pad(&mut world, &[Event::Axis(PadAxis::RightStickX, 1.0)]);
+		assert_eq!(world.menu_choice, Some(right));
+		pad(&mut world, &[Event::Released(PadButton::LeftTrigger2)]);
+		assert_eq!((world.menu_choice, world.elements.clone()), (None, vec![up, right]));
+	}
+
+	#[test]
+	fn trigger_script_removes_its_other() {
+		let shock = || triggers::Kind::Switch(spells::Element::Shock);
+		let remover = script("on enter\nremove(other)\nend");
+		let mut world = world(vec![], vec![trigger(shock(), remover), trigger(shock(), None)]);
+		world.elements = vec![spells::Element::Shock];
+		let spell = world.cast().unwrap();
+		for _ in 0..3 {
+			world.tick();
+		}
+		assert!(!world.objects.contains_key(&spell));
+	}
+
+	#[test]
+	fn platform_doesnt_carry_the_rider_into_walls() {
+		let mut platform = block(0.0, 0.0, None);
+		platform.size = Vector::new(0.6, 0.1);
+		let (offset, period) = (Vector::new(1.0, 0.0), 2.0);
+		platform.motion = Some(platforms::Motion::Oscillate { offset, period });
+		let mut wall = block(0.3, 0.3, None);
+		wall.size = Vector::new(0.1, 0.4);
+		let mut world = world(vec![platform, wall], vec![]);
+		world.objects.get_mut(&world.player_id).unwrap().pos = Vector::new(0.0, 0.15);
+		for _ in 0..50 {
+			world.move_platforms(1.0 / world.config.tickrate);
+			let (_, max) = world.objects.get(&world.player_id).unwrap().bounds();
+			assert!(max.x <= 0.25 + 0.001, "the player went into the wall, to {}", max.x);
+		}
+		// carried up against it rather than left behind
+		let (_, max) = world.objects.get(&world.player_id).unwrap().bounds();
+		assert!(max.x > 0.25 - 0.001);
+	}
+
+	#[test]
+	fn platform_pushes_what_it_runs_into() {
+		let mut floor = block(0.0, 0.0, None);
+		floor.size = Vector::new(4.0, 0.1);
+		let mut platform = block(0.0, 0.15, None);
+		let (offset, period) = (Vector::new(1.0, 0.0), 2.0);
+		platform.motion = Some(platforms::Motion::Oscillate { offset, period });
+		let mut world = world(vec![floor, platform], vec![]);
+		world.objects.get_mut(&world.player_id).unwrap().pos = Vector::new(0.3, 0.15);
+		let platform = world.ground_ids[1];
+		for _ in 0..50 {
+			world.move_platforms(1.0 / world.config.tickrate);
+			let (player_min, player_max) = world.objects.get(&world.player_id).unwrap().bounds();
+			let (min, max) = world.objects.get(&platform).unwrap().bounds();
+			assert!(!object::aabb_aabb(player_min, player_max, min, max));
+		}
+		// pushed along in front of it
+		let player = world.objects.get(&world.player_id).unwrap();
+		assert!(player.pos.x > 1.1 && (player.pos.y - 0.15).abs() < 0.01);
+	}
+
+	#[test]
+	fn carved_pieces_keep_what_the_block_was() {
+		let mut door = block(0.0, 0.0, script("on tick\nend"));
+		door.size = Vector::new(0.6, 0.2);
+		door.color = [0.1, 0.2, 0.3];
+		let (offset, period) = (Vector::new(0.0, 1.0), 2.0);
+		door.motion = Some(platforms::Motion::Oscillate { offset, period });
+		let mut world = world(vec![door], vec![]);
+		let id = world.ground_ids[0];
+		world.burning.insert(id, 0.25);
+		world.carve(Vector::new(-0.1, -0.2), Vector::new(0.1, 0.2));
+		assert_eq!(world.ground_ids.len(), 2);
+		for piece in world.ground_ids.clone() {
+			let pos = world.objects.get(&piece).unwrap().pos;
+			assert_eq!(world.colors.get(&piece), Some(&[0.1, 0.2, 0.3]));
+			assert!(world.scripts.contains_key(&piece));
+			assert_eq!(world.burning.get(&piece), Some(&0.25));
+			let platform = world.platforms.get(&piece).unwrap().pos();
+			assert_eq!((platform.x, platform.y), (pos.x, pos.y));
+		}
+		assert!(world.terrain_changed.contains(&id));
+	}
+
+	#[test]
+	fn saving_twice_writes_the_same_file() {
+		let ground = (0..8).map(|i| block(i as f32, 0.0, None)).collect();
+		let triggers = (0..8)
+			.map(|i| level::Trigger {
+				pos: Vector::new(i as f32, 2.0),
+				..trigger(triggers::Kind::Damage(i as f32), None)
+			})
+			.collect();
+		let path = std::env::temp_dir().join(format!("feto-{}.txt", std::process::id()));
+		let path = path.to_str().unwrap();
+		world(ground, triggers).level().save(path).unwrap();
+		let first = std::fs::read_to_string(path).unwrap();
+		let mut again = World::new(config::Config::default());
+		again.load_level(&level::Level::load(path).unwrap());
+		again.level().save(path).unwrap();
+		let second = std::fs::read_to_string(path).unwrap();
+		std::fs::remove_file(path).unwrap();
+		assert_eq!(first, second);
+		assert!(first.find("trigger 0 ").unwrap() < first.find("trigger 7 ").unwrap());
+	}
+
+	#[test]
+	fn melting_ice_is_redrawn() {
+		let ice = level::Block { material: materials::Material::Ice, ..block(0.0, 0.0, None) };
+		let mut world = world(vec![ice], vec![]);
+		let id = world.ground_ids[0];
+		world.terrain_changed.clear();
+		world.ignite(id);
+		assert!(world.terrain_changed.contains(&id));
+		world.terrain_changed.clear();
+		world.extinguish(id);
+		assert!(world.terrain_changed.contains(&id));
+	}
+
+	#[test]
+	fn failed_commands_leave_the_world_alone() {
+		let mut world = world(vec![], vec![]);
+		assert!(console::execute(&mut world, "step 1e12").is_err());
+		assert!(console::execute(&mut world, "step -1").is_err());
+		world.elements = vec![spells::Element::Fire];
+		world.mana = 0.0;
+		assert!(console::execute(&mut world, "cast water air").is_err());
+		assert_eq!(world.elements, [spells::Element::Fire]);
+	}
+
+	#[test]
+	fn trajectory_doesnt_depend_on_the_tickrate() {
+		let wall = level::Block { size: Vector::new(0.2, 2.0), ..block(1.0, 5.0, None) };
+		let mut world = world(vec![wall], vec![]);
+		world.mouse = Vector::new(1.0, 5.0);
+		world.elements = vec![spells::Element::Fire, spells::Element::Fire];
+		let points = world.trajectory();
+		let mut config = world.config.clone();
+		config.set("tickrate", 1000.0).unwrap();
+		world.set_config(config);
+		let again = world.trajectory();
+		assert_eq!(points.len(), again.len());
+		let (end, again) = (points[points.len() - 1], again[again.len() - 1]);
+		assert_eq!((end.x, end.y), (again.x, again.y));
+		// stopped by the wall rather than carrying on for its whole range
+		assert!(end.x < 0.9, "{:?}", end);
+	}
+
+	#[test]
+	fn tick_script_removes_a_later_script() {
+		let remover = script("on tick\nremove(block_at(2, 0))\nend");
+		let mut world =
+			world(vec![block(0.0, 0.0, remover), block(2.0, 0.0, script(""))], vec![]);
+		world.tick();
+		assert_eq!(world.ground_ids.len(), 1);
+		world.tick();
+	}
 }
@@ -0,0 +1,68 @@
+use crate::render::Color;
+
+// what a piece of ground is made of; ground without one is stone
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Material {
+	Stone,
+	Wood,
+	Grass,
+	Ice,
+}
+
+// what's left of a block once it has finished burning
+pub enum Remains {
+	Nothing,
+	Material(Material),
+	Water, // as much as the block held
+}
+
+impl Material {
+	pub const ALL: [Material; 4] =
+		[Material::Stone, Material::Wood, Material::Grass, Material::Ice];
+
+	pub fn from_name(name: &str) -> Option<Material> {
+		Material::ALL.iter().find(|material| material.name() == name).copied()
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Material::Stone => "stone",
+			Material::Wood => "wood",
+			Material::Grass => "grass",
+			Material::Ice => "ice",
+		}
+	}
+
+	// tints the ground sprite
+	pub fn color(self) -> Color {
+		match self {
+			Material::Stone => [1.0, 1.0, 1.0],
+			Material::Wood => [0.8, 0.55, 0.3],
+			Material::Grass => [0.5, 0.9, 0.4],
+			Material::Ice => [0.7, 0.9, 1.0],
+		}
+	}
+
+	// seconds it takes to burn through once lit, or none if fire doesn't touch it
+	pub fn burn_time(self) -> Option<f32> {
+		match self {
+			Material::Stone => None,
+			Material::Wood => Some(4.0),
+			Material::Grass => Some(1.0),
+			Material::Ice => Some(0.5),
+		}
+	}
+
+	// whether it catches its neighbours alight while burning, rather than just melting
+	pub fn spreads_fire(self) -> bool {
+		matches!(self, Material::Wood | Material::Grass)
+	}
+
+	pub fn remains(self) -> Remains {
+		match self {
+			Material::Stone | Material::Wood => Remains::Nothing,
+			Material::Grass => Remains::Material(Material::Stone), // scorched bare
+			Material::Ice => Remains::Water,
+		}
+	}
+}
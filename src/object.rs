@@ -18,6 +18,20 @@ impl Object {
 		Object { pos: Vector { x, y }, vel: Vector::new(0.0, 0.0), shape }
 	}
 
+	// the smallest box containing the shape, as its min and max corners
+	pub fn bounds(&self) -> (Vector, Vector) {
+		match self.shape {
+			Shape::Aabb(size) => (self.pos - size * 0.5, self.pos + size * 0.5),
+			Shape::Line(dir) => {
+				let end = self.pos + dir;
+				(
+					Vector::new(self.pos.x.min(end.x), self.pos.y.min(end.y)),
+					Vector::new(self.pos.x.max(end.x), self.pos.y.max(end.y)),
+				)
+			}
+		}
+	}
+
 	pub fn move_and_collide(&mut self, colliders: &[Object], delta_time: f32) -> bool {
 		let mut collided = false;
 		self.pos += self.vel * delta_time;
@@ -59,7 +73,7 @@ impl Object {
 	}
 }
 
-fn aabb_aabb(amin: Vector, amax: Vector, bmin: Vector, bmax: Vector) -> bool {
+pub fn aabb_aabb(amin: Vector, amax: Vector, bmin: Vector, bmax: Vector) -> bool {
 	amin.x < bmax.x && amax.x > bmin.x && amin.y < bmax.y && amax.y > bmin.y
 }
 
@@ -77,7 +91,7 @@ fn line_aabb(pos: Vector, dir: Vector, min: Vector, max: Vector) -> bool {
 	let t1 = (max - pos) * d;
 	let tmin = f32::max(f32::min(t0.x, t1.x), f32::min(t0.y, t1.y));
 	let tmax = f32::min(f32::max(t0.x, t1.x), f32::max(t0.y, t1.y));
-	return tmin <= t && t <= tmax;
+	tmin <= t && t <= tmax
 }
 
 pub fn collide(a: &Object, b: &Object) -> bool {
@@ -0,0 +1,25 @@
+struct VertexInput {
+	[[location(0)]] corner: vec2<f32>;
+	[[location(1)]] pos: vec2<f32>;
+	[[location(2)]] color: vec4<f32>;
+	[[location(3)]] size: f32;
+};
+
+struct VertexOutput {
+	[[builtin(position)]] pos: vec4<f32>;
+	[[location(0)]] color: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn vertex(input: VertexInput) -> VertexOutput {
+	var output: VertexOutput;
+	// render.rs has already aspect corrected both the corner and the position
+	output.pos = vec4<f32>(input.pos + input.corner * input.size, 0.0, 1.0);
+	output.color = input.color;
+	return output;
+}
+
+[[stage(fragment)]]
+fn fragment(input: VertexOutput) -> [[location(0)]] vec4<f32> {
+	return input.color;
+}
@@ -0,0 +1,230 @@
+use crate::random::Rng;
+use crate::render::Color;
+use crate::spells::Element;
+use crate::vector::Vector;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Preset {
+	pub lifetime: f32,  // seconds each particle lives
+	pub speed: f32,     // initial speed along the emission direction
+	pub direction: f32, // radians, 0 is +x
+	pub spread: f32,    // radians either side of the direction
+	pub gravity: f32,   // downwards acceleration, negative to rise
+	pub inherit: f32,   // fraction of the source velocity added to each particle
+	pub size: f32,
+	pub start: Color,
+	pub end: Color,
+}
+
+#[derive(Clone)]
+pub struct Emitter {
+	pub preset: Preset,
+	pub rate: f32,     // particles per second
+	pub lifetime: f32, // seconds until the emitter stops, infinite while attached
+	pub accumulator: f32,
+}
+
+pub struct Particle {
+	pub pos: Vector,
+	pub vel: Vector,
+	pub age: f32,
+	pub preset: Preset,
+}
+
+const MAX_PARTICLES: usize = 4096; // the oldest go first past this
+
+pub struct Particles {
+	pub particles: Vec<Particle>,
+	rng: Rng,
+}
+
+// per-instance data for the instanced particle draw
+#[repr(C)]
+pub struct Instance {
+	pub color: [f32; 4],
+	pub size: f32,
+}
+
+impl Emitter {
+	pub fn new(preset: Preset, rate: f32) -> Emitter {
+		Emitter { preset, rate, lifetime: f32::INFINITY, accumulator: 0.0 }
+	}
+}
+
+impl Particle {
+	// color fades from the preset's start to its end color and out to transparent
+	pub fn color(&self) -> [f32; 4] {
+		let t = (self.age / self.preset.lifetime).min(1.0);
+		let (s, e) = (self.preset.start, self.preset.end);
+		[s[0] + (e[0] - s[0]) * t, s[1] + (e[1] - s[1]) * t, s[2] + (e[2] - s[2]) * t, 1.0 - t]
+	}
+}
+
+impl Particles {
+	pub fn new(seed: u32) -> Particles {
+		Particles { particles: vec![], rng: Rng::new(seed) }
+	}
+
+	pub fn emit(&mut self, pos: Vector, source_vel: Vector, preset: Preset, count: usize) {
+		for _ in 0..count {
+			let angle = preset.direction + self.rng.range(-preset.spread, preset.spread);
+			let speed = preset.speed * self.rng.range(0.5, 1.0);
+			self.particles.push(Particle {
+				pos,
+				vel: Vector::new(angle.cos(), angle.sin()) * speed + source_vel * preset.inherit,
+				age: self.rng.range(0.0, preset.lifetime * 0.25),
+				preset,
+			});
+		}
+		// particles are only ever pushed, so the oldest are at the front
+		let excess = self.particles.len().saturating_sub(MAX_PARTICLES);
+		self.particles.drain(..excess);
+	}
+
+	// emits from an emitter at a position, returning false once the emitter has expired
+	pub fn run_emitter(
+		&mut self,
+		emitter: &mut Emitter,
+		pos: Vector,
+		source_vel: Vector,
+		delta_time: f32,
+	) -> bool {
+		emitter.lifetime -= delta_time;
+		emitter.accumulator += emitter.rate * delta_time;
+		let count = emitter.accumulator.floor();
+		emitter.accumulator -= count;
+		self.emit(pos, source_vel, emitter.preset, count as usize);
+		emitter.lifetime > 0.0
+	}
+
+	pub fn step(&mut self, delta_time: f32) {
+		for particle in &mut self.particles {
+			particle.age += delta_time;
+			particle.vel.y -= particle.preset.gravity * delta_time;
+			particle.pos += particle.vel * delta_time;
+		}
+		self.particles.retain(|particle| particle.age < particle.preset.lifetime);
+	}
+}
+
+const TAU: f32 = std::f32::consts::TAU;
+const UP: f32 = std::f32::consts::FRAC_PI_2;
+
+// the trail left behind a spell in flight
+pub fn trail(element: Element) -> Emitter {
+	let base = Preset {
+		lifetime: 0.4,
+		speed: 0.1,
+		direction: 0.0,
+		spread: TAU / 2.0,
+		gravity: 0.0,
+		inherit: 0.0,
+		size: 0.02,
+		start: element.color(),
+		end: element.color(),
+	};
+	let (preset, rate) = match element {
+		Element::Earth => {
+			(Preset { gravity: 2.0, size: 0.025, end: [0.2, 0.1, 0.0], ..base }, 30.0)
+		}
+		Element::Water => (Preset { gravity: 3.0, inherit: 0.2, ..base }, 60.0),
+		Element::Air => (Preset { lifetime: 0.25, speed: 0.3, size: 0.015, ..base }, 40.0),
+		Element::Fire => (
+			Preset {
+				direction: UP,
+				spread: 0.6,
+				speed: 0.4,
+				gravity: -1.0,
+				start: [1.0, 0.8, 0.1],
+				end: [0.6, 0.0, 0.0],
+				..base
+			},
+			80.0,
+		),
+		Element::Acid => (Preset { gravity: 4.0, lifetime: 0.6, size: 0.015, ..base }, 40.0),
+		Element::Pressure => (Preset { lifetime: 0.2, speed: 0.5, size: 0.01, ..base }, 60.0),
+		Element::Shock => (
+			Preset { lifetime: 0.1, speed: 1.0, size: 0.01, start: [1.0, 1.0, 1.0], ..base },
+			100.0,
+		),
+		Element::Radiance => (Preset { lifetime: 0.6, speed: 0.05, ..base }, 40.0),
+		Element::Life => (
+			Preset { direction: UP, spread: 0.8, speed: 0.2, end: [0.4, 1.0, 0.4], ..base },
+			30.0,
+		),
+		Element::Void => {
+			(Preset { lifetime: 0.5, speed: 0.05, start: [0.3, 0.0, 0.3], ..base }, 40.0)
+		}
+	};
+	Emitter::new(preset, rate)
+}
+
+// the burst where a spell lands, and how many particles are in it
+pub fn impact(element: Element) -> (Preset, usize) {
+	let base = Preset {
+		lifetime: 0.5,
+		speed: 1.0,
+		direction: 0.0,
+		spread: TAU / 2.0,
+		gravity: 5.0,
+		inherit: 0.0,
+		size: 0.02,
+		start: element.color(),
+		end: element.color(),
+	};
+	match element {
+		Element::Earth => (Preset { speed: 0.8, size: 0.03, end: [0.2, 0.1, 0.0], ..base }, 20),
+		Element::Water => (Preset { speed: 1.2, gravity: 8.0, ..base }, 40),
+		Element::Air => (Preset { speed: 1.5, gravity: 0.0, lifetime: 0.3, ..base }, 30),
+		Element::Fire => {
+			(Preset { gravity: -2.0, start: [1.0, 0.8, 0.1], end: [0.3, 0.0, 0.0], ..base }, 40)
+		}
+		Element::Acid => (Preset { speed: 0.6, lifetime: 0.8, ..base }, 30),
+		Element::Pressure => (Preset { speed: 2.5, gravity: 0.0, lifetime: 0.2, ..base }, 40),
+		Element::Shock => (
+			Preset { speed: 2.0, gravity: 0.0, lifetime: 0.15, start: [1.0, 1.0, 1.0], ..base },
+			30,
+		),
+		Element::Radiance => (Preset { speed: 0.5, gravity: 0.0, lifetime: 0.8, ..base }, 30),
+		Element::Life => (
+			Preset { direction: UP, spread: 1.0, gravity: -1.0, end: [0.4, 1.0, 0.4], ..base },
+			25,
+		),
+		Element::Void => (
+			Preset { speed: 0.3, gravity: 0.0, lifetime: 0.6, start: [0.3, 0.0, 0.3], ..base },
+			30,
+		),
+	}
+}
+
+// the puff where fire meets water
+pub fn steam() -> (Preset, usize) {
+	let preset = Preset {
+		lifetime: 0.8,
+		speed: 0.4,
+		direction: UP,
+		spread: 0.8,
+		gravity: -1.0,
+		inherit: 0.0,
+		size: 0.03,
+		start: [0.9, 0.9, 0.9],
+		end: [0.5, 0.5, 0.5],
+	};
+	(preset, 40)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn emitting_past_the_cap_drops_the_oldest() {
+		let mut particles = Particles::new(1);
+		let (preset, _) = steam();
+		particles.emit(Vector::new(-1.0, 0.0), Vector::new(0.0, 0.0), preset, MAX_PARTICLES);
+		particles.emit(Vector::new(1.0, 0.0), Vector::new(0.0, 0.0), preset, 10);
+		assert_eq!(particles.particles.len(), MAX_PARTICLES);
+		assert!(particles.particles[MAX_PARTICLES - 10..].iter().all(|p| p.pos.x == 1.0));
+		assert_eq!(particles.particles.iter().filter(|p| p.pos.x == 1.0).count(), 10);
+	}
+}
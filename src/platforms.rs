@@ -0,0 +1,137 @@
+use crate::level::numbers;
+use crate::vector::Vector;
+use std::fmt;
+
+const TAU: f32 = std::f32::consts::TAU;
+
+// how a moving platform gets around, relative to where it starts
+#[derive(Clone, Debug)]
+pub enum Motion {
+	// through each offset in turn at a steady speed, then back to the start
+	Path { speed: f32, points: Vec<Vector> },
+	// out to the offset, back past the start to the opposite side, and back, once a period
+	Oscillate { offset: Vector, period: f32 },
+}
+
+pub struct Platform {
+	pub origin: Vector,
+	pub motion: Motion,
+	pub time: f32,               // seconds it has been moving
+	legs: Vec<(Vector, Vector)>, // of a path, from each stop to the next and back to the start
+}
+
+fn positive(number: f32) -> bool {
+	number.is_finite() && number > 0.0
+}
+
+impl Motion {
+	pub fn parse(words: &[&str]) -> Result<Motion, String> {
+		match words {
+			["path", rest @ ..] => match numbers(rest)?.as_slice() {
+				[speed, offsets @ ..] if !offsets.is_empty() && offsets.len() % 2 == 0 => {
+					if !positive(*speed) {
+						return Err(format!("the speed has to be more than 0, not {}", speed));
+					}
+					let points = offsets.chunks(2).map(|xy| Vector::new(xy[0], xy[1])).collect();
+					Ok(Motion::Path { speed: *speed, points })
+				}
+				_ => Err("expected `path speed dx dy [dx dy]...`".to_string()),
+			},
+			["oscillate", rest @ ..] => match numbers(rest)?.as_slice() {
+				// pos() divides by the period
+				[_, _, period] if !positive(*period) => {
+					Err(format!("the period has to be more than 0, not {}", period))
+				}
+				[dx, dy, period] => {
+					Ok(Motion::Oscillate { offset: Vector::new(*dx, *dy), period: *period })
+				}
+				_ => Err("expected `oscillate dx dy period`".to_string()),
+			},
+			_ => Err("expected `path` or `oscillate`".to_string()),
+		}
+	}
+}
+
+impl fmt::Display for Motion {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Motion::Path { speed, points } => {
+				write!(f, "path {}", speed)?;
+				for point in points {
+					write!(f, " {} {}", point.x, point.y)?;
+				}
+				Ok(())
+			}
+			Motion::Oscillate { offset, period } => {
+				write!(f, "oscillate {} {} {}", offset.x, offset.y, period)
+			}
+		}
+	}
+}
+
+impl Platform {
+	pub fn new(origin: Vector, motion: Motion) -> Platform {
+		let legs = match &motion {
+			Motion::Path { points, .. } => {
+				let stops: Vec<Vector> =
+					[Vector::new(0.0, 0.0)].into_iter().chain(points.iter().copied()).collect();
+				stops.iter().zip(stops.iter().cycle().skip(1)).map(|(a, b)| (*a, *b)).collect()
+			}
+			Motion::Oscillate { .. } => vec![],
+		};
+		Platform { origin, motion, time: 0.0, legs }
+	}
+
+	// where the platform is at its time
+	pub fn pos(&self) -> Vector {
+		let offset = match &self.motion {
+			Motion::Oscillate { offset, period } => *offset * (TAU * self.time / period).sin(),
+			Motion::Path { speed, .. } => {
+				let length: f32 = self.legs.iter().map(|(a, b)| (*b - *a).length()).sum();
+				if length <= 0.0 {
+					return self.origin;
+				}
+				let mut distance = (self.time * speed).rem_euclid(length);
+				let mut offset = Vector::new(0.0, 0.0);
+				for (a, b) in &self.legs {
+					let leg = (*b - *a).length();
+					if distance < leg {
+						offset = *a + (*b - *a) * (distance / leg);
+						break;
+					}
+					distance -= leg;
+				}
+				offset
+			}
+		};
+		self.origin + offset
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_rejects_motions_that_never_move() {
+		assert!(Motion::parse(&["oscillate", "1", "0", "0"]).is_err());
+		assert!(Motion::parse(&["oscillate", "1", "0", "-2"]).is_err());
+		assert!(Motion::parse(&["path", "0", "1", "0"]).is_err());
+		assert!(Motion::parse(&["path", "nan", "1", "0"]).is_err());
+		let motion = Motion::parse(&["oscillate", "1", "0", "2"]).unwrap();
+		let mut platform = Platform::new(Vector::new(0.0, 0.0), motion);
+		platform.time = 0.5;
+		assert_eq!(platform.pos().x, 1.0);
+	}
+
+	#[test]
+	fn path_goes_there_and_back() {
+		let motion = Motion::parse(&["path", "1", "1", "0", "1", "1"]).unwrap();
+		let mut platform = Platform::new(Vector::new(2.0, 0.0), motion);
+		for (time, x, y) in [(0.5, 2.5, 0.0), (1.5, 3.0, 0.5), (2.0 + 0.5f32.sqrt(), 2.5, 0.5)] {
+			platform.time = time;
+			let pos = platform.pos();
+			assert!((pos.x - x).abs() < 1e-4 && (pos.y - y).abs() < 1e-4, "{:?}", pos);
+		}
+	}
+}
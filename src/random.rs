@@ -0,0 +1,22 @@
+// xorshift32: small, fast, and deterministic for a given seed
+pub struct Rng {
+	state: u32,
+}
+
+impl Rng {
+	pub fn new(seed: u32) -> Rng {
+		Rng { state: seed.max(1) }
+	}
+
+	pub fn next(&mut self) -> u32 {
+		self.state ^= self.state << 13;
+		self.state ^= self.state >> 17;
+		self.state ^= self.state << 5;
+		self.state
+	}
+
+	// uniform in [min, max)
+	pub fn range(&mut self, min: f32, max: f32) -> f32 {
+		min + (self.next() >> 8) as f32 / (1 << 24) as f32 * (max - min)
+	}
+}
@@ -0,0 +1,103 @@
+use crate::spells::Element;
+
+// what happens when the first element of a reaction meets the second
+#[derive(Clone, Copy, Debug)]
+pub enum Outcome {
+	// both boil away
+	Steam,
+	// the first charges the water around it for this many seconds per point of its duration,
+	// and the second lands as if it hit something
+	Electrify(f32),
+	// the first takes the second over and grows by the factor
+	Spread(f32),
+	// the second is destroyed
+	Dissolve,
+	// both vanish
+	Cancel,
+}
+
+pub struct Reaction {
+	pub first: Option<Element>, // none matches any other element
+	pub second: Option<Element>,
+	pub outcome: Outcome,
+}
+
+pub struct Reactions {
+	pub reactions: Vec<Reaction>,
+}
+
+impl Outcome {
+	fn parse(words: &[&str]) -> Result<Outcome, String> {
+		let value = || match words.get(1) {
+			Some(word) => word.parse().map_err(|_| format!("bad number `{}`", word)),
+			None => Err(format!("`{}` needs a value", words[0])),
+		};
+		Ok(match words[0] {
+			"steam" => Outcome::Steam,
+			"electrify" => Outcome::Electrify(value()?),
+			"spread" => Outcome::Spread(value()?),
+			"dissolve" => Outcome::Dissolve,
+			"cancel" => Outcome::Cancel,
+			word => return Err(format!("unknown outcome `{}`", word)),
+		})
+	}
+}
+
+impl Reaction {
+	fn matches(&self, a: Element, b: Element) -> bool {
+		let matches = |pattern: Option<Element>, element: Element, other: Element| {
+			pattern.map_or(element != other, |pattern| pattern == element)
+		};
+		matches(self.first, a, b) && matches(self.second, b, a)
+	}
+}
+
+impl Reactions {
+	// each line is `element element : outcome [value]`, for example `fire water : steam`;
+	// `*` stands for any element other than the one it meets
+	pub fn load(path: &str) -> Result<Reactions, String> {
+		let file = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+		let mut reactions = vec![];
+		for (i, line) in file.lines().enumerate() {
+			let error = |message: String| format!("{}:{}: {}", path, i + 1, message);
+			let line = line.split('#').next().unwrap();
+			if line.trim().is_empty() {
+				continue;
+			}
+			let (head, tail) = line.split_once(':').unwrap_or((line, ""));
+			let elements = head
+				.split_whitespace()
+				.map(|word| match word {
+					"*" => Ok(None),
+					_ => Element::from_name(word)
+						.map(Some)
+						.ok_or_else(|| error(format!("unknown element `{}`", word))),
+				})
+				.collect::<Result<Vec<Option<Element>>, String>>()?;
+			if elements.len() != 2 {
+				return Err(error("expected two elements".to_string()));
+			}
+			let words: Vec<&str> = tail.split_whitespace().collect();
+			if words.is_empty() {
+				return Err(error("expected an outcome after `:`".to_string()));
+			}
+			let outcome = Outcome::parse(&words).map_err(error)?;
+			reactions.push(Reaction { first: elements[0], second: elements[1], outcome });
+		}
+		Ok(Reactions { reactions })
+	}
+
+	// the first reaction between the two elements, and whether they're the other way
+	// around from how the reaction lists them
+	pub fn find(&self, a: Element, b: Element) -> Option<(Outcome, bool)> {
+		self.reactions.iter().find_map(|reaction| {
+			if reaction.matches(a, b) {
+				Some((reaction.outcome, false))
+			} else if reaction.matches(b, a) {
+				Some((reaction.outcome, true))
+			} else {
+				None
+			}
+		})
+	}
+}
@@ -0,0 +1,154 @@
+use crate::spells::{Behaviour, Element, Spell, SpellError};
+
+// a named spell made by queueing exactly these elements in this order
+pub struct Recipe {
+	pub name: String,
+	pub elements: Vec<Element>,
+	pub properties: Vec<(Property, f32)>,
+	pub behaviours: Vec<Behaviour>, // added to what the elements give
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Property {
+	// multiply the stat that the elements produce
+	Range,
+	Speed,
+	Cost,
+	Strength,
+	Duration,
+	Area,
+	Gravity,
+	// replaces the radius of the light the spell carries
+	Light,
+}
+
+pub struct Recipes {
+	pub recipes: Vec<Recipe>,
+}
+
+impl Property {
+	fn from_name(name: &str) -> Option<Property> {
+		Some(match name {
+			"range" => Property::Range,
+			"speed" => Property::Speed,
+			"cost" => Property::Cost,
+			"strength" => Property::Strength,
+			"duration" => Property::Duration,
+			"area" => Property::Area,
+			"gravity" => Property::Gravity,
+			"light" => Property::Light,
+			_ => return None,
+		})
+	}
+}
+
+impl Recipe {
+	pub fn apply(&self, spell: &mut Spell) {
+		spell.name = Some(self.name.clone());
+		for (property, value) in &self.properties {
+			match property {
+				Property::Range => spell.range *= value,
+				Property::Speed => spell.speed *= value,
+				Property::Cost => spell.cost *= value,
+				Property::Strength => spell.strength *= value,
+				Property::Duration => spell.duration *= value,
+				Property::Area => spell.area *= value,
+				Property::Gravity => spell.gravity *= value,
+				Property::Light => spell.light = *value,
+			}
+		}
+		spell.behaviours.extend(self.behaviours.iter().copied());
+	}
+}
+
+impl Recipes {
+	// each line is `name element... : property value...`, for example
+	// `fireball fire air : speed 2 light 0.6 behaviour split`
+	pub fn load(path: &str) -> Result<Recipes, String> {
+		let file = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+		Recipes::parse(path, &file)
+	}
+
+	pub fn parse(path: &str, file: &str) -> Result<Recipes, String> {
+		let mut recipes = vec![];
+		for (i, line) in file.lines().enumerate() {
+			let error = |message: String| format!("{}:{}: {}", path, i + 1, message);
+			let line = line.split('#').next().unwrap();
+			if line.trim().is_empty() {
+				continue;
+			}
+			let (head, tail) = line.split_once(':').unwrap_or((line, ""));
+			let words: Vec<&str> = head.split_whitespace().collect();
+			if words.len() < 2 {
+				return Err(error("expected a name and at least one element".to_string()));
+			}
+			let name = words[0].to_string();
+			let elements = words[1..]
+				.iter()
+				.map(|word| {
+					Element::from_name(word)
+						.ok_or_else(|| error(format!("unknown element `{}`", word)))
+				})
+				.collect::<Result<Vec<Element>, String>>()?;
+			let words: Vec<&str> = tail.split_whitespace().collect();
+			if words.len() % 2 == 1 {
+				return Err(error("expected pairs of properties and values".to_string()));
+			}
+			let (mut properties, mut behaviours) = (vec![], vec![]);
+			for pair in words.chunks(2) {
+				if pair[0] == "behaviour" {
+					let behaviour = Behaviour::from_name(pair[1])
+						.ok_or_else(|| error(format!("unknown behaviour `{}`", pair[1])))?;
+					behaviours.push(behaviour);
+					continue;
+				}
+				let property = Property::from_name(pair[0])
+					.ok_or_else(|| error(format!("unknown property `{}`", pair[0])))?;
+				let value =
+					pair[1].parse().map_err(|_| error(format!("bad number `{}`", pair[1])))?;
+				properties.push((property, value));
+			}
+			recipes.push(Recipe { name, elements, properties, behaviours });
+		}
+		Ok(Recipes { recipes })
+	}
+
+	pub fn find(&self, elements: &[Element]) -> Option<&Recipe> {
+		self.recipes.iter().find(|recipe| recipe.elements == elements)
+	}
+
+	// the spell that the elements make, named if they follow a recipe
+	pub fn cast(&self, elements: &[Element], max_modifiers: usize) -> Result<Spell, SpellError> {
+		let mut spell = Spell::new(elements, max_modifiers)?;
+		if let Some(recipe) = self.find(elements) {
+			recipe.apply(&mut spell);
+			spell.settle();
+		}
+		Ok(spell)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn behaviours() {
+		let file =
+			"seeker shock air : speed 2 behaviour homing behaviour split\nwall fire : speed 0";
+		let recipes = Recipes::parse("test", file).unwrap();
+		let behaviours = &recipes.recipes[0].behaviours;
+		assert_eq!(behaviours, &[Behaviour::Homing(3.0), Behaviour::Split(3)]);
+
+		let seeker = recipes.cast(&[Element::Shock, Element::Air], 4).unwrap();
+		assert_eq!(seeker.name.as_deref(), Some("seeker"));
+		assert!(seeker.behaviours.contains(&Behaviour::Split(3)));
+		assert_eq!(seeker.shards(), 3);
+		// no speed left after the recipe, so it stays put
+		let wall = recipes.cast(&[Element::Fire], 4).unwrap();
+		assert!(wall.is_stationary());
+
+		let error = Recipes::parse("test", "a fire : behaviour flying").err();
+		assert_eq!(error.as_deref(), Some("test:1: unknown behaviour `flying`"));
+	}
+}
@@ -1,15 +1,20 @@
+use crate::shaders::Shaders;
 use crate::vector::Vector;
 
 pub struct State {
+	#[allow(dead_code)]
 	pub instance: wgpu::Instance,
 	pub surface: wgpu::Surface,
+	#[allow(dead_code)]
 	pub adapter: wgpu::Adapter,
 	pub device: wgpu::Device,
 	pub queue: wgpu::Queue,
 	pub config: wgpu::SurfaceConfiguration,
+	pub shaders: Shaders,
 }
 
 #[repr(C)]
+#[derive(Clone, Copy)]
 pub struct Vertex<VertexData> {
 	pub pos: Vector,
 	pub data: VertexData,
@@ -17,6 +22,19 @@ pub struct Vertex<VertexData> {
 
 pub type Color = [f32; 3];
 
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Textured {
+	pub uv: Vector,
+	pub color: Color,
+}
+
+pub struct Texture {
+	pub view: wgpu::TextureView,
+	pub layout: wgpu::BindGroupLayout,
+	pub bind_group: wgpu::BindGroup,
+}
+
 impl State {
 	pub async fn new(window: &winit::window::Window) -> State {
 		let instance = wgpu::Instance::new(wgpu::Backends::all());
@@ -48,7 +66,7 @@ impl State {
 			present_mode: wgpu::PresentMode::Fifo,
 		};
 		surface.configure(&device, &config);
-		State { instance, surface, adapter, device, queue, config }
+		State { instance, surface, adapter, device, queue, config, shaders: Shaders::new() }
 	}
 
 	pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
@@ -57,6 +75,41 @@ impl State {
 		self.surface.configure(&self.device, &self.config);
 	}
 
+	pub fn clear(
+		&self,
+		encoder: &mut wgpu::CommandEncoder,
+		view: &wgpu::TextureView,
+		color: Color,
+	) {
+		encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+			label: Some("Clear"),
+			color_attachments: &[wgpu::RenderPassColorAttachment {
+				view,
+				resolve_target: None,
+				ops: wgpu::Operations {
+					load: wgpu::LoadOp::Clear(wgpu::Color {
+						r: color[0] as f64,
+						g: color[1] as f64,
+						b: color[2] as f64,
+						a: 1.0,
+					}),
+					store: true,
+				},
+			}],
+			depth_stencil_attachment: None,
+		});
+	}
+
+	// half the width and height of the world area that is visible on screen
+	pub fn view_extent(&self) -> Vector {
+		let aspect = self.config.height as f32 / self.config.width as f32;
+		if aspect < 1.0 {
+			Vector::new(1.0 / aspect, 1.0)
+		} else {
+			Vector::new(1.0, aspect)
+		}
+	}
+
 	pub fn render<VertexData>(
 		&self,
 		encoder: &mut wgpu::CommandEncoder,
@@ -64,15 +117,98 @@ impl State {
 		shader: &wgpu::ShaderModuleDescriptor,
 		vertex_layout: &[wgpu::VertexAttribute],
 		vertices: Vec<Vertex<VertexData>>,
+		blend: wgpu::BlendState,
 	) {
+		self.draw::<VertexData, ()>(
+			encoder,
+			view,
+			shader,
+			vertex_layout,
+			vertices,
+			None,
+			None,
+			blend,
+		)
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	pub fn render_textured<VertexData>(
+		&self,
+		encoder: &mut wgpu::CommandEncoder,
+		view: &wgpu::TextureView,
+		shader: &wgpu::ShaderModuleDescriptor,
+		vertex_layout: &[wgpu::VertexAttribute],
+		vertices: Vec<Vertex<VertexData>>,
+		texture: &Texture,
+		blend: wgpu::BlendState,
+	) {
+		self.draw::<VertexData, ()>(
+			encoder,
+			view,
+			shader,
+			vertex_layout,
+			vertices,
+			None,
+			Some(texture),
+			blend,
+		)
+	}
+
+	// draws the vertices once per instance, with the vertices and each instance's position
+	// aspect corrected, so offsets from the position built out of the vertices are too
+	#[allow(clippy::too_many_arguments)]
+	pub fn render_instanced<VertexData, InstanceData>(
+		&self,
+		encoder: &mut wgpu::CommandEncoder,
+		view: &wgpu::TextureView,
+		shader: &wgpu::ShaderModuleDescriptor,
+		vertex_layout: &[wgpu::VertexAttribute],
+		vertices: Vec<Vertex<VertexData>>,
+		instance_layout: &[wgpu::VertexAttribute],
+		instances: Vec<Vertex<InstanceData>>,
+	) {
+		self.draw(
+			encoder,
+			view,
+			shader,
+			vertex_layout,
+			vertices,
+			Some((instance_layout, instances)),
+			None,
+			wgpu::BlendState::ALPHA_BLENDING,
+		)
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	fn draw<VertexData, InstanceData>(
+		&self,
+		encoder: &mut wgpu::CommandEncoder,
+		view: &wgpu::TextureView,
+		shader: &wgpu::ShaderModuleDescriptor,
+		vertex_layout: &[wgpu::VertexAttribute],
+		vertices: Vec<Vertex<VertexData>>,
+		instances: Option<(&[wgpu::VertexAttribute], Vec<Vertex<InstanceData>>)>,
+		texture: Option<&Texture>,
+		blend: wgpu::BlendState,
+	) {
+		if vertices.is_empty() || instances.as_ref().is_some_and(|(_, i)| i.is_empty()) {
+			return;
+		}
 		let mut vertices = vertices;
+		let mut instances = instances;
 		let aspect = self.config.height as f32 / self.config.width as f32;
-		for vertex in &mut vertices {
+		let correct = |pos: &mut Vector| {
 			if aspect < 1.0 {
-				vertex.pos.x *= aspect;
+				pos.x *= aspect;
 			} else {
-				vertex.pos.y /= aspect;
+				pos.y /= aspect;
 			}
+		};
+		for vertex in &mut vertices {
+			correct(&mut vertex.pos);
+		}
+		for instance in instances.iter_mut().flat_map(|(_, i)| i.iter_mut()) {
+			correct(&mut instance.pos);
 		}
 		use wgpu::util::DeviceExt;
 		let vertex_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -80,19 +216,40 @@ impl State {
 			contents: bytes(&vertices),
 			usage: wgpu::BufferUsages::VERTEX,
 		});
+		let instance_buffer = instances.as_ref().map(|(_, instances)| {
+			self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+				label: Some("Instance Buffer"),
+				contents: bytes(instances),
+				usage: wgpu::BufferUsages::VERTEX,
+			})
+		});
+		let mut buffers = vec![wgpu::VertexBufferLayout {
+			array_stride: std::mem::size_of::<Vertex<VertexData>>() as wgpu::BufferAddress,
+			step_mode: wgpu::VertexStepMode::Vertex,
+			attributes: vertex_layout,
+		}];
+		if let Some((instance_layout, _)) = &instances {
+			buffers.push(wgpu::VertexBufferLayout {
+				array_stride: std::mem::size_of::<Vertex<InstanceData>>() as wgpu::BufferAddress,
+				step_mode: wgpu::VertexStepMode::Instance,
+				attributes: instance_layout,
+			});
+		}
 		let shader = self.device.create_shader_module(shader);
+		let layout = texture.map(|texture| {
+			self.device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+				label: None,
+				bind_group_layouts: &[&texture.layout],
+				push_constant_ranges: &[],
+			})
+		});
 		let pipeline = self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
 			label: None,
-			layout: None,
+			layout: layout.as_ref(),
 			vertex: wgpu::VertexState {
 				module: &shader,
 				entry_point: "vertex",
-				buffers: &[wgpu::VertexBufferLayout {
-					array_stride: std::mem::size_of::<Vertex<VertexData>>()
-						as wgpu::BufferAddress,
-					step_mode: wgpu::VertexStepMode::Vertex,
-					attributes: &vertex_layout,
-				}],
+				buffers: &buffers,
 			},
 			primitive: wgpu::PrimitiveState {
 				topology: wgpu::PrimitiveTopology::TriangleList,
@@ -114,7 +271,7 @@ impl State {
 				entry_point: "fragment",
 				targets: &[wgpu::ColorTargetState {
 					format: self.config.format,
-					blend: Some(wgpu::BlendState::REPLACE),
+					blend: Some(blend),
 					write_mask: wgpu::ColorWrites::ALL,
 				}],
 			}),
@@ -123,15 +280,103 @@ impl State {
 		let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
 			label: None,
 			color_attachments: &[wgpu::RenderPassColorAttachment {
-				view: &view,
+				view,
 				resolve_target: None,
 				ops: wgpu::Operations { load: wgpu::LoadOp::Load, store: true },
 			}],
 			depth_stencil_attachment: None,
 		});
 		render_pass.set_pipeline(&pipeline);
+		if let Some(texture) = texture {
+			render_pass.set_bind_group(0, &texture.bind_group, &[]);
+		}
 		render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-		render_pass.draw(0..vertices.len() as u32, 0..1);
+		if let Some(instance_buffer) = &instance_buffer {
+			render_pass.set_vertex_buffer(1, instance_buffer.slice(..));
+		}
+		let instance_count = instances.map_or(1, |(_, instances)| instances.len());
+		render_pass.draw(0..vertices.len() as u32, 0..instance_count as u32);
+	}
+
+	// pixels are rgba8 in srgb, sampled with nearest filtering to keep pixel art crisp
+	pub fn create_texture(&self, width: u32, height: u32, pixels: &[u8]) -> Texture {
+		use wgpu::util::DeviceExt;
+		let texture = self.device.create_texture_with_data(
+			&self.queue,
+			&wgpu::TextureDescriptor {
+				label: Some("Texture"),
+				size: wgpu::Extent3d { width, height, depth_or_array_layers: 1 },
+				mip_level_count: 1,
+				sample_count: 1,
+				dimension: wgpu::TextureDimension::D2,
+				format: wgpu::TextureFormat::Rgba8UnormSrgb,
+				usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+			},
+			pixels,
+		);
+		self.bind_texture(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+	}
+
+	// an offscreen texture the size of the surface that can be rendered to and then sampled
+	pub fn create_target(&self) -> Texture {
+		let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+			label: Some("Target"),
+			size: wgpu::Extent3d {
+				width: self.config.width,
+				height: self.config.height,
+				depth_or_array_layers: 1,
+			},
+			mip_level_count: 1,
+			sample_count: 1,
+			dimension: wgpu::TextureDimension::D2,
+			format: self.config.format,
+			usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+		});
+		self.bind_texture(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+	}
+
+	fn bind_texture(&self, view: wgpu::TextureView) -> Texture {
+		let sampler = self.device.create_sampler(&wgpu::SamplerDescriptor {
+			mag_filter: wgpu::FilterMode::Nearest,
+			min_filter: wgpu::FilterMode::Nearest,
+			..Default::default()
+		});
+		let layout = self.device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+			label: None,
+			entries: &[
+				wgpu::BindGroupLayoutEntry {
+					binding: 0,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Texture {
+						sample_type: wgpu::TextureSampleType::Float { filterable: true },
+						view_dimension: wgpu::TextureViewDimension::D2,
+						multisampled: false,
+					},
+					count: None,
+				},
+				wgpu::BindGroupLayoutEntry {
+					binding: 1,
+					visibility: wgpu::ShaderStages::FRAGMENT,
+					ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+					count: None,
+				},
+			],
+		});
+		let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+			label: None,
+			layout: &layout,
+			entries: &[
+				wgpu::BindGroupEntry {
+					binding: 0,
+					resource: wgpu::BindingResource::TextureView(&view),
+				},
+				wgpu::BindGroupEntry {
+					binding: 1,
+					resource: wgpu::BindingResource::Sampler(&sampler),
+				},
+			],
+		});
+		Texture { view, layout, bind_group }
 	}
 }
 
@@ -145,7 +390,7 @@ fn bytes<T: Sized>(slice: &[T]) -> &[u8] {
 	unsafe {
 		std::slice::from_raw_parts(
 			(slice as *const [T]) as *const u8,
-			slice.len() * std::mem::size_of::<T>(),
+			std::mem::size_of_val(slice),
 		)
 	}
 }
@@ -0,0 +1,659 @@
+use crate::lighting::Light;
+use crate::object;
+use crate::vector::Vector;
+use crate::{Id, World};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// scripts are made of event handlers:
+//
+//   on hit
+//   	if element == "shock" and not opened
+//   		opened = 1
+//   		move(self, x(self), y(self) + 0.3)
+//   	end
+//   end
+//
+// the events are `load`, `tick` (with `dt`), `touch` while the player is against the object,
+// `hit` when a spell lands on it (with its `element`), and for triggers `enter` and `exit`
+// (with the `other` object's id and its `element` if it's a spell); `self` is the object's id
+//
+// there are no loops, so every handler finishes, and scripts can only reach the world through
+// the functions in `call`; variables keep their values between events
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+	Number(f32), // also true (1) and false (0)
+	Text(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+	Number(f32),
+	Text(String),
+	Name(String),
+	Symbol(&'static str),
+}
+
+enum Expr {
+	Value(Value),
+	Variable(String),
+	Call(String, Vec<Expr>),
+	Not(Box<Expr>),
+	Negate(Box<Expr>),
+	Binary(&'static str, Box<Expr>, Box<Expr>),
+}
+
+enum Statement {
+	Assign(String, Expr),
+	If(Expr, Block, Block),
+	Expr(Expr),
+}
+
+type Block = Vec<(usize, Statement)>; // statements by line number
+
+pub struct Program {
+	pub path: String,
+	handlers: HashMap<String, Block>,
+}
+
+// a program attached to an object, with its own variables
+#[derive(Clone)]
+pub struct Instance {
+	pub program: Rc<Program>,
+	pub age: f32, // seconds the object has been ticking
+	variables: HashMap<String, Value>,
+	failed: bool, // a handler ran into an error, so the script is stopped
+}
+
+// longest first, so that `==` isn't read as two `=`
+const SYMBOLS: [&str; 15] =
+	["==", "!=", "<=", ">=", "<", ">", "=", "+", "-", "*", "/", "%", "(", ")", ","];
+
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+	let mut tokens = vec![];
+	let mut rest = line.trim_start();
+	while let Some(c) = rest.chars().next() {
+		if c == '#' {
+			break;
+		} else if c == '"' {
+			let end = rest[1..].find('"').ok_or("unterminated text")?;
+			tokens.push(Token::Text(rest[1..end + 1].to_string()));
+			rest = &rest[end + 2..];
+		} else if c.is_ascii_digit() || c == '.' {
+			let end = rest.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(rest.len());
+			let number =
+				rest[..end].parse().map_err(|_| format!("bad number `{}`", &rest[..end]))?;
+			tokens.push(Token::Number(number));
+			rest = &rest[end..];
+		} else if c.is_alphabetic() || c == '_' {
+			let end =
+				rest.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(rest.len());
+			tokens.push(Token::Name(rest[..end].to_string()));
+			rest = &rest[end..];
+		} else {
+			let symbol = SYMBOLS
+				.iter()
+				.find(|symbol| rest.starts_with(**symbol))
+				.ok_or_else(|| format!("unexpected `{}`", c))?;
+			tokens.push(Token::Symbol(symbol));
+			rest = &rest[symbol.len()..];
+		}
+		rest = rest.trim_start();
+	}
+	Ok(tokens)
+}
+
+// operators from loosest to tightest binding
+const PRECEDENCE: [&[&str]; 5] =
+	[&["or"], &["and"], &["==", "!=", "<", ">", "<=", ">="], &["+", "-"], &["*", "/", "%"]];
+
+struct Parser<'a> {
+	tokens: &'a [Token],
+	next: usize,
+}
+
+impl Parser<'_> {
+	fn peek(&self) -> Option<&Token> {
+		self.tokens.get(self.next)
+	}
+
+	fn eat(&mut self, symbol: &str) -> bool {
+		let found = matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol);
+		if found {
+			self.next += 1;
+		}
+		found
+	}
+
+	// the operator at the given precedence level coming up next, if there is one
+	fn operator(&self, level: usize) -> Option<&'static str> {
+		PRECEDENCE[level].iter().copied().find(|op| match self.peek() {
+			Some(Token::Symbol(symbol)) => symbol == op,
+			Some(Token::Name(name)) => name == op,
+			_ => false,
+		})
+	}
+
+	fn expr(&mut self, level: usize) -> Result<Expr, String> {
+		if level == PRECEDENCE.len() {
+			return self.unary();
+		}
+		let mut left = self.expr(level + 1)?;
+		while let Some(op) = self.operator(level) {
+			self.next += 1;
+			let right = self.expr(level + 1)?;
+			left = Expr::Binary(op, Box::new(left), Box::new(right));
+		}
+		Ok(left)
+	}
+
+	fn unary(&mut self) -> Result<Expr, String> {
+		if self.eat("-") {
+			return Ok(Expr::Negate(Box::new(self.unary()?)));
+		}
+		if matches!(self.peek(), Some(Token::Name(name)) if name == "not") {
+			self.next += 1;
+			// `not` covers a whole comparison, so `not a == b` reads as it sounds
+			return Ok(Expr::Not(Box::new(self.expr(2)?)));
+		}
+		let token = self.peek().cloned().ok_or("expected a value")?;
+		self.next += 1;
+		match token {
+			Token::Number(number) => Ok(Expr::Value(Value::Number(number))),
+			Token::Text(text) => Ok(Expr::Value(Value::Text(text))),
+			Token::Symbol("(") => {
+				let inner = self.expr(0)?;
+				match self.eat(")") {
+					true => Ok(inner),
+					false => Err("expected `)`".to_string()),
+				}
+			}
+			Token::Name(name) if self.eat("(") => {
+				let mut args = vec![];
+				if !self.eat(")") {
+					loop {
+						args.push(self.expr(0)?);
+						if self.eat(")") {
+							break;
+						}
+						if !self.eat(",") {
+							return Err("expected `,` or `)`".to_string());
+						}
+					}
+				}
+				Ok(Expr::Call(name, args))
+			}
+			Token::Name(name) => Ok(Expr::Variable(name)),
+			Token::Symbol(symbol) => Err(format!("unexpected `{}`", symbol)),
+		}
+	}
+}
+
+fn parse_expr(tokens: &[Token]) -> Result<Expr, String> {
+	let mut parser = Parser { tokens, next: 0 };
+	let expr = parser.expr(0)?;
+	match parser.peek() {
+		None => Ok(expr),
+		Some(_) => Err("expected the end of the line".to_string()),
+	}
+}
+
+fn word(tokens: &[Token]) -> Option<&str> {
+	match tokens.first() {
+		Some(Token::Name(name)) => Some(name),
+		_ => None,
+	}
+}
+
+// the statements up to an `end` or `else`, and whether it was an `else`
+fn parse_block(
+	lines: &mut impl Iterator<Item = (usize, Vec<Token>)>,
+) -> Result<(Block, bool), (usize, String)> {
+	let mut statements = vec![];
+	while let Some((line, tokens)) = lines.next() {
+		let error = |message: String| (line, message);
+		let statement = match (word(&tokens), tokens.get(1)) {
+			(None, None) => continue,
+			(Some("end"), None) => return Ok((statements, false)),
+			(Some("else"), None) => return Ok((statements, true)),
+			(Some("on"), _) => return Err(error("`on` inside a handler".to_string())),
+			(Some("if"), _) => {
+				let condition = parse_expr(&tokens[1..]).map_err(error)?;
+				let (then, has_else) = parse_block(lines)?;
+				let otherwise = match has_else {
+					true => match parse_block(lines)? {
+						(otherwise, false) => otherwise,
+						(_, true) => return Err(error("`if` with two `else`s".to_string())),
+					},
+					false => vec![],
+				};
+				Statement::If(condition, then, otherwise)
+			}
+			(Some(name), Some(Token::Symbol("="))) => {
+				Statement::Assign(name.to_string(), parse_expr(&tokens[2..]).map_err(error)?)
+			}
+			_ => Statement::Expr(parse_expr(&tokens).map_err(error)?),
+		};
+		statements.push((line, statement));
+	}
+	Err((0, "missing `end`".to_string()))
+}
+
+impl Program {
+	pub fn load(path: &str) -> Result<Program, String> {
+		let file = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+		Program::parse(path, &file)
+	}
+
+	// the path is only for error messages and for knowing which objects to reload
+	pub fn parse(path: &str, file: &str) -> Result<Program, String> {
+		let mut lines = vec![];
+		for (i, line) in file.lines().enumerate() {
+			let tokens = tokenize(line).map_err(|e| format!("{}:{}: {}", path, i + 1, e))?;
+			lines.push((i + 1, tokens));
+		}
+		let total = lines.len();
+		let mut lines = lines.into_iter();
+		let mut handlers = HashMap::new();
+		while let Some((line, tokens)) = lines.next() {
+			let error = |(line, message)| match line {
+				0 => format!("{}:{}: {}", path, total, message),
+				_ => format!("{}:{}: {}", path, line, message),
+			};
+			match tokens.as_slice() {
+				[] => {}
+				[Token::Name(on), Token::Name(event)] if on == "on" => {
+					let (statements, has_else) = parse_block(&mut lines).map_err(error)?;
+					if has_else {
+						return Err(error((line, "`else` without `if`".to_string())));
+					}
+					handlers.insert(event.clone(), statements);
+				}
+				_ => return Err(error((line, "expected `on event`".to_string()))),
+			}
+		}
+		Ok(Program { path: path.to_string(), handlers })
+	}
+}
+
+fn number(value: &Value) -> Result<f32, String> {
+	match value {
+		Value::Number(number) => Ok(*number),
+		Value::Text(text) => Err(format!("expected a number, not \"{}\"", text)),
+	}
+}
+
+fn truth(value: &Value) -> bool {
+	match value {
+		Value::Number(number) => *number != 0.0,
+		Value::Text(text) => !text.is_empty(),
+	}
+}
+
+fn boolean(b: bool) -> Value {
+	Value::Number(if b { 1.0 } else { 0.0 })
+}
+
+fn binary(op: &str, a: Value, b: Value) -> Result<Value, String> {
+	match op {
+		"==" => return Ok(boolean(a == b)),
+		"!=" => return Ok(boolean(a != b)),
+		"and" => return Ok(boolean(truth(&a) && truth(&b))),
+		"or" => return Ok(boolean(truth(&a) || truth(&b))),
+		_ => {}
+	}
+	let (a, b) = (number(&a)?, number(&b)?);
+	Ok(match op {
+		"<" => boolean(a < b),
+		">" => boolean(a > b),
+		"<=" => boolean(a <= b),
+		">=" => boolean(a >= b),
+		"+" => Value::Number(a + b),
+		"-" => Value::Number(a - b),
+		"*" => Value::Number(a * b),
+		"/" => Value::Number(a / b),
+		_ => Value::Number(a % b),
+	})
+}
+
+// the id of an object that still exists
+fn id(world: &World, value: &Value) -> Result<Id, String> {
+	let id = number(value)? as Id;
+	match world.objects.contains_key(&id) {
+		true => Ok(id),
+		false => Err(format!("no object {}", id)),
+	}
+}
+
+impl Instance {
+	pub fn new(program: Rc<Program>) -> Instance {
+		Instance { program, age: 0.0, variables: HashMap::new(), failed: false }
+	}
+
+	// runs the handler for an event, if the script has one, with some variables set for it;
+	// an error stops the script for good rather than repeating every tick
+	pub fn run(&mut self, world: &mut World, me: Id, event: &str, locals: &[(&str, Value)]) {
+		let program = self.program.clone();
+		let statements = match program.handlers.get(event) {
+			Some(statements) if !self.failed => statements,
+			_ => return,
+		};
+		self.variables.insert("self".to_string(), Value::Number(me as f32));
+		for (name, value) in locals {
+			self.variables.insert(name.to_string(), value.clone());
+		}
+		if let Err((line, e)) = self.block(world, statements) {
+			eprintln!("{}:{}: {}", program.path, line, e);
+			self.failed = true;
+		}
+	}
+
+	fn block(
+		&mut self,
+		world: &mut World,
+		statements: &[(usize, Statement)],
+	) -> Result<(), (usize, String)> {
+		for (line, statement) in statements {
+			let error = |e| (*line, e);
+			match statement {
+				Statement::Assign(name, expr) => {
+					let value = self.eval(world, expr).map_err(error)?;
+					self.variables.insert(name.clone(), value);
+				}
+				Statement::If(condition, then, otherwise) => {
+					match truth(&self.eval(world, condition).map_err(error)?) {
+						true => self.block(world, then)?,
+						false => self.block(world, otherwise)?,
+					}
+				}
+				Statement::Expr(expr) => {
+					self.eval(world, expr).map_err(error)?;
+				}
+			}
+		}
+		Ok(())
+	}
+
+	fn eval(&mut self, world: &mut World, expr: &Expr) -> Result<Value, String> {
+		match expr {
+			Expr::Value(value) => Ok(value.clone()),
+			Expr::Variable(name) => self
+				.variables
+				.get(name)
+				.cloned()
+				.ok_or_else(|| format!("unknown variable `{}`", name)),
+			Expr::Not(inner) => Ok(boolean(!truth(&self.eval(world, inner)?))),
+			Expr::Negate(inner) => Ok(Value::Number(-number(&self.eval(world, inner)?)?)),
+			// `and` and `or` only look at their right side when they need to
+			Expr::Binary("and", a, b) => match truth(&self.eval(world, a)?) {
+				true => Ok(boolean(truth(&self.eval(world, b)?))),
+				false => Ok(boolean(false)),
+			},
+			Expr::Binary("or", a, b) => match truth(&self.eval(world, a)?) {
+				true => Ok(boolean(true)),
+				false => Ok(boolean(truth(&self.eval(world, b)?))),
+			},
+			Expr::Binary(op, a, b) => {
+				let a = self.eval(world, a)?;
+				binary(op, a, self.eval(world, b)?)
+			}
+			Expr::Call(name, args) => {
+				let args = args
+					.iter()
+					.map(|arg| self.eval(world, arg))
+					.collect::<Result<Vec<Value>, String>>()?;
+				self.call(world, name, &args)
+			}
+		}
+	}
+
+	// everything a script can ask of or do to the world
+	fn call(&mut self, world: &mut World, name: &str, args: &[Value]) -> Result<Value, String> {
+		let arity = match name {
+			"time" | "player" | "mana" => 0,
+			"sin" | "cos" | "abs" | "sqrt" | "floor" | "exists" | "x" | "y" | "width"
+			| "height" | "vx" | "vy" | "remove" | "ignite" | "extinguish" | "give_mana" => 1,
+			"min" | "max" | "touching" | "block_at" => 2,
+			"move" | "velocity" | "water" => 3,
+			"color" => 4,
+			"light" => 5,
+			"print" => args.len(),
+			_ => return Err(format!("unknown function `{}`", name)),
+		};
+		if args.len() != arity {
+			return Err(format!("`{}` takes {} arguments, not {}", name, arity, args.len()));
+		}
+		let n = |i: usize| number(&args[i]);
+		let done = Ok(Value::Number(0.0));
+		match name {
+			"sin" => Ok(Value::Number(n(0)?.sin())),
+			"cos" => Ok(Value::Number(n(0)?.cos())),
+			"abs" => Ok(Value::Number(n(0)?.abs())),
+			"sqrt" => Ok(Value::Number(n(0)?.sqrt())),
+			"floor" => Ok(Value::Number(n(0)?.floor())),
+			"min" => Ok(Value::Number(n(0)?.min(n(1)?))),
+			"max" => Ok(Value::Number(n(0)?.max(n(1)?))),
+			"time" => Ok(Value::Number(self.age)),
+			"player" => Ok(Value::Number(world.player_id as f32)),
+			"mana" => Ok(Value::Number(world.mana)),
+			"exists" => Ok(boolean(world.objects.contains_key(&(n(0)? as Id)))),
+			// the ground block at a point, or -1 if there isn't one
+			"block_at" => {
+				let point = Vector::new(n(0)?, n(1)?);
+				let block = world.terrain.query(point, point).into_iter().find(|id| {
+					let (min, max) = world.objects.get(id).unwrap().bounds();
+					min.x <= point.x && point.x <= max.x && min.y <= point.y && point.y <= max.y
+				});
+				Ok(Value::Number(block.map_or(-1.0, |id| id as f32)))
+			}
+			"x" | "y" | "width" | "height" | "vx" | "vy" => {
+				let object = world.objects.get(&id(world, &args[0])?).unwrap();
+				let (min, max) = object.bounds();
+				Ok(Value::Number(match name {
+					"x" => object.pos.x,
+					"y" => object.pos.y,
+					"width" => max.x - min.x,
+					"height" => max.y - min.y,
+					"vx" => object.vel.x,
+					_ => object.vel.y,
+				}))
+			}
+			// standing on something counts as touching it
+			"touching" => {
+				let margin = world.config.ground_check;
+				let a = world.objects.get(&id(world, &args[0])?).unwrap().bounds();
+				let b = world.objects.get(&id(world, &args[1])?).unwrap().bounds();
+				Ok(boolean(object::aabb_aabb(a.0 - margin, a.1 + margin, b.0, b.1)))
+			}
+			"move" => {
+				let id = id(world, &args[0])?;
+				let pos = Vector::new(n(1)?, n(2)?);
+				let mut object = world.objects.get(&id).unwrap().clone();
+				object.pos = pos;
+				match world.ground_ids.contains(&id) {
+					true => world.move_ground(id, object),
+					false => {
+						world.objects.insert(id, object);
+					}
+				}
+				done
+			}
+			"velocity" => {
+				let id = id(world, &args[0])?;
+				world.objects.get_mut(&id).unwrap().vel = Vector::new(n(1)?, n(2)?);
+				done
+			}
+			"color" => {
+				let id = id(world, &args[0])?;
+				world.colors.insert(id, [n(1)?, n(2)?, n(3)?]);
+				world.terrain_changed.insert(id);
+				done
+			}
+			"light" => {
+				let id = id(world, &args[0])?;
+				world.lights.insert(id, Light::new([n(1)?, n(2)?, n(3)?], n(4)?));
+				done
+			}
+			"remove" => {
+				let id = id(world, &args[0])?;
+				world.remove(id)?;
+				done
+			}
+			"ignite" => {
+				let id = id(world, &args[0])?;
+				world.ignite(id);
+				done
+			}
+			"extinguish" => {
+				let id = id(world, &args[0])?;
+				world.extinguish(id);
+				done
+			}
+			"water" => {
+				world.fluid.add(Vector::new(n(0)?, n(1)?), n(2)?);
+				done
+			}
+			"give_mana" => {
+				world.mana = (world.mana + n(0)?).min(world.config.max_mana);
+				done
+			}
+			_ => {
+				let text: Vec<String> = args
+					.iter()
+					.map(|arg| match arg {
+						Value::Number(number) => number.to_string(),
+						Value::Text(text) => text.clone(),
+					})
+					.collect();
+				eprintln!("{}: {}", self.program.path, text.join(" "));
+				done
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::Config;
+
+	fn eval(world: &mut World, line: &str) -> Result<Value, String> {
+		let expr = parse_expr(&tokenize(line)?)?;
+		let mut instance = Instance::new(Rc::new(Program::parse("test", "").unwrap()));
+		instance.eval(world, &expr)
+	}
+
+	fn number(line: &str) -> f32 {
+		match eval(&mut World::new(Config::default()), line) {
+			Ok(Value::Number(number)) => number,
+			other => panic!("`{}` gave {:?}", line, other),
+		}
+	}
+
+	fn error(source: &str) -> String {
+		match Program::parse("test", source) {
+			Ok(_) => panic!("parsed:\n{}", source),
+			Err(e) => e,
+		}
+	}
+
+	#[test]
+	fn tokenize_splits_lines() {
+		let tokens = tokenize("x = max(1.5, \"a b\") == y # comment").unwrap();
+		assert_eq!(
+			tokens,
+			[
+				Token::Name("x".to_string()),
+				Token::Symbol("="),
+				Token::Name("max".to_string()),
+				Token::Symbol("("),
+				Token::Number(1.5),
+				Token::Symbol(","),
+				Token::Text("a b".to_string()),
+				Token::Symbol(")"),
+				Token::Symbol("=="),
+				Token::Name("y".to_string()),
+			]
+		);
+		assert_eq!(tokenize("a<=b").unwrap()[1], Token::Symbol("<="));
+		assert!(tokenize("   # only a comment").unwrap().is_empty());
+	}
+
+	#[test]
+	fn tokenize_errors() {
+		assert_eq!(tokenize("print(\"open").unwrap_err(), "unterminated text");
+		assert_eq!(tokenize("1.2.3").unwrap_err(), "bad number `1.2.3`");
+		assert_eq!(tokenize("a & b").unwrap_err(), "unexpected `&`");
+	}
+
+	#[test]
+	fn precedence() {
+		assert_eq!(number("1 + 2 * 3"), 7.0);
+		assert_eq!(number("(1 + 2) * 3"), 9.0);
+		assert_eq!(number("10 - 4 - 3"), 3.0);
+		assert_eq!(number("7 % 4 * 2"), 6.0);
+		assert_eq!(number("-2 * 3 + 1"), -5.0);
+		assert_eq!(number("1 + 1 == 2 and 3 > 2"), 1.0);
+		assert_eq!(number("0 and 1 or 1"), 1.0);
+		assert_eq!(number("1 or 0 and 0"), 1.0);
+	}
+
+	#[test]
+	fn not() {
+		assert_eq!(number("not 0"), 1.0);
+		assert_eq!(number("not 3"), 0.0);
+		assert_eq!(number("not 1 == 2"), 1.0);
+		assert_eq!(number("not 1 == 1 or 1"), 1.0);
+		assert_eq!(number("not \"\""), 1.0);
+	}
+
+	#[test]
+	fn and_or_short_circuit() {
+		let mut world = World::new(Config::default());
+		assert_eq!(eval(&mut world, "0 and missing"), Ok(Value::Number(0.0)));
+		assert_eq!(eval(&mut world, "1 or missing"), Ok(Value::Number(1.0)));
+		assert_eq!(eval(&mut world, "0 and give_mana(-5)"), Ok(Value::Number(0.0)));
+		assert_eq!(world.mana, world.config.max_mana);
+		assert_eq!(eval(&mut world, "1 and missing").unwrap_err(), "unknown variable `missing`");
+		assert_eq!(eval(&mut world, "0 or missing").unwrap_err(), "unknown variable `missing`");
+	}
+
+	#[test]
+	fn if_else_end_errors() {
+		assert_eq!(error("on tick\nif 1\nx = 1\nend\n"), "test:4: missing `end`");
+		assert_eq!(
+			error("on tick\nif 1\nelse\nelse\nend\nend"),
+			"test:2: `if` with two `else`s"
+		);
+		assert_eq!(error("on tick\nelse\nend"), "test:1: `else` without `if`");
+		assert_eq!(error("on tick\non load\nend"), "test:2: `on` inside a handler");
+		assert_eq!(error("x = 1"), "test:1: expected `on event`");
+		assert_eq!(error("on tick\nif\nend\nend"), "test:2: expected a value");
+		assert!(Program::parse("test", "on tick\nif 1\nelse\nend\nend").is_ok());
+	}
+
+	#[test]
+	fn arity_errors() {
+		let mut world = World::new(Config::default());
+		assert_eq!(eval(&mut world, "sin(1, 2)").unwrap_err(), "`sin` takes 1 arguments, not 2");
+		assert_eq!(eval(&mut world, "time(1)").unwrap_err(), "`time` takes 0 arguments, not 1");
+		assert_eq!(
+			eval(&mut world, "color(0)").unwrap_err(),
+			"`color` takes 4 arguments, not 1"
+		);
+		assert_eq!(eval(&mut world, "jump()").unwrap_err(), "unknown function `jump`");
+		assert_eq!(eval(&mut world, "min(1, 2)"), Ok(Value::Number(1.0)));
+	}
+
+	#[test]
+	fn errors_stop_the_script() {
+		let mut world = World::new(Config::default());
+		let source = "on tick\ncount = count + 1\nend\non load\ncount = 0\nend";
+		let mut instance = Instance::new(Rc::new(Program::parse("test", source).unwrap()));
+		instance.run(&mut world, 0, "tick", &[]);
+		assert!(instance.failed);
+		instance.run(&mut world, 0, "load", &[]);
+		assert_eq!(instance.variables.get("count"), None);
+	}
+}
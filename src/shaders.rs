@@ -0,0 +1,238 @@
+use naga::{Binding, Module, ResourceBinding, ShaderStage, TypeInner};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+pub const DIR: &str = "src";
+
+// wgsl sources by file name, baked in at build time and replaced whenever the files change
+pub struct Shaders {
+	sources: HashMap<&'static str, String>,
+}
+
+// something about a shader that the pipelines built from it depend on
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Part {
+	Entry(ShaderStage, String),
+	Input(ShaderStage, u32), // by location
+	Output(ShaderStage, u32),
+	Resource(ResourceBinding),
+}
+
+impl Part {
+	// what's passed from the vertex to the fragment stage is up to the shader itself
+	fn is_internal(&self) -> bool {
+		matches!(
+			self,
+			Part::Output(ShaderStage::Vertex, _) | Part::Input(ShaderStage::Fragment, _)
+		)
+	}
+}
+
+impl fmt::Display for Part {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let name = |stage: &ShaderStage| format!("{:?}", stage).to_lowercase();
+		match self {
+			Part::Entry(stage, entry) => write!(f, "{} entry point `{}`", name(stage), entry),
+			Part::Input(stage, location) => write!(f, "{} input {}", name(stage), location),
+			Part::Output(stage, location) => write!(f, "{} output {}", name(stage), location),
+			Part::Resource(binding) => {
+				write!(f, "binding {} in group {}", binding.binding, binding.group)
+			}
+		}
+	}
+}
+
+// a type written out in full, so that types from different modules can be compared
+fn describe(module: &Module, ty: naga::Handle<naga::Type>) -> String {
+	match &module.types[ty].inner {
+		TypeInner::Struct { members, .. } => {
+			let members: Vec<String> =
+				members.iter().map(|member| describe(module, member.ty)).collect();
+			format!("struct {{ {} }}", members.join(", "))
+		}
+		TypeInner::Array { base, size, stride } => {
+			format!("array<{}, {:?}, {}>", describe(module, *base), size, stride)
+		}
+		inner => format!("{:?}", inner),
+	}
+}
+
+// the locations a value with this binding, or the members of its struct, are passed in
+fn locations(
+	module: &Module,
+	ty: naga::Handle<naga::Type>,
+	binding: &Option<Binding>,
+) -> Vec<(u32, String)> {
+	match (binding, &module.types[ty].inner) {
+		(Some(Binding::Location { location, .. }), _) => vec![(*location, describe(module, ty))],
+		(None, TypeInner::Struct { members, .. }) => members
+			.iter()
+			.flat_map(|member| locations(module, member.ty, &member.binding))
+			.collect(),
+		_ => vec![],
+	}
+}
+
+// every part of a shader the pipelines depend on, with its type
+fn interface(module: &Module) -> BTreeMap<Part, String> {
+	let mut parts = BTreeMap::new();
+	for entry in &module.entry_points {
+		let (stage, function) = (entry.stage, &entry.function);
+		parts.insert(Part::Entry(stage, entry.name.clone()), String::new());
+		for argument in &function.arguments {
+			for (location, ty) in locations(module, argument.ty, &argument.binding) {
+				parts.insert(Part::Input(stage, location), ty);
+			}
+		}
+		if let Some(result) = &function.result {
+			for (location, ty) in locations(module, result.ty, &result.binding) {
+				parts.insert(Part::Output(stage, location), ty);
+			}
+		}
+	}
+	for (_, variable) in module.global_variables.iter() {
+		if let Some(binding) = &variable.binding {
+			parts.insert(Part::Resource(binding.clone()), describe(module, variable.ty));
+		}
+	}
+	parts
+}
+
+// naga doesn't compare a shader with the pipelines it's used in, so a shader that validates
+// but has different entry points, inputs or bindings would fail in wgpu instead
+fn compatible(old: &Module, new: &Module) -> Result<(), String> {
+	let (old, new) = (interface(old), interface(new));
+	for (part, ty) in old.iter().filter(|(part, _)| !part.is_internal()) {
+		match new.get(part) {
+			None => return Err(format!("missing the {}", part)),
+			Some(new_ty) if new_ty != ty => {
+				return Err(format!("the {} should be `{}`, not `{}`", part, ty, new_ty))
+			}
+			_ => {}
+		}
+	}
+	for (part, ty) in &new {
+		match part {
+			Part::Input(ShaderStage::Fragment, location) => {
+				match new.get(&Part::Output(ShaderStage::Vertex, *location)) {
+					Some(output) if output == ty => {}
+					_ => return Err(format!("the {} isn't a matching vertex output", part)),
+				}
+			}
+			_ if !part.is_internal() && !old.contains_key(part) => {
+				return Err(format!("the {} isn't one the pipeline has", part))
+			}
+			_ => {}
+		}
+	}
+	Ok(())
+}
+
+impl Shaders {
+	pub fn new() -> Shaders {
+		Shaders {
+			sources: HashMap::from([
+				("flat.wgsl", include_str!("flat.wgsl").to_string()),
+				("textured.wgsl", include_str!("textured.wgsl").to_string()),
+				("particle.wgsl", include_str!("particle.wgsl").to_string()),
+				("light.wgsl", include_str!("light.wgsl").to_string()),
+			]),
+		}
+	}
+
+	pub fn paths(&self) -> Vec<String> {
+		self.sources.keys().map(|name| format!("{}/{}", DIR, name)).collect()
+	}
+
+	pub fn get<'a>(&'a self, name: &'a str) -> wgpu::ShaderModuleDescriptor<'a> {
+		wgpu::ShaderModuleDescriptor {
+			label: Some(name),
+			source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(&self.sources[name])),
+		}
+	}
+
+	// rereads a shader from disk, keeping the old one if the new one doesn't validate or doesn't
+	// fit the pipelines
+	pub fn reload(&mut self, path: &str) -> Result<(), String> {
+		let name = self
+			.sources
+			.keys()
+			.copied()
+			.find(|name| path == format!("{}/{}", DIR, name))
+			.ok_or_else(|| format!("{}: not a shader", path))?;
+		let source = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+		let module = naga::front::wgsl::parse_str(&source)
+			.map_err(|e| format!("{}:{}: {}", path, e.location(&source).0, e))?;
+		naga::valid::Validator::new(
+			naga::valid::ValidationFlags::all(),
+			naga::valid::Capabilities::empty(),
+		)
+		.validate(&module)
+		.map_err(|e| format!("{}: {}", path, e))?;
+		// the source in use always parsed once already
+		let old = naga::front::wgsl::parse_str(&self.sources[name]).unwrap();
+		compatible(&old, &module).map_err(|e| format!("{}: {}", path, e))?;
+		self.sources.insert(name, source);
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn check(edit: impl Fn(&str) -> String) -> Result<(), String> {
+		let source = include_str!("flat.wgsl");
+		let old = naga::front::wgsl::parse_str(source).unwrap();
+		let new = naga::front::wgsl::parse_str(&edit(source)).unwrap();
+		compatible(&old, &new)
+	}
+
+	#[test]
+	fn unchanged() {
+		assert_eq!(check(|source| source.to_string()), Ok(()));
+	}
+
+	#[test]
+	fn renamed_entry_point() {
+		let result = check(|source| source.replace("fn vertex(", "fn vert("));
+		assert_eq!(result, Err("missing the vertex entry point `vertex`".to_string()));
+	}
+
+	#[test]
+	fn changed_vertex_input() {
+		let result = check(|source| source.replace("color: vec3<f32>;", "color: vec4<f32>;"));
+		assert!(result.unwrap_err().starts_with("the vertex input 1 should be"));
+	}
+
+	#[test]
+	fn extra_vertex_input() {
+		let result = check(|source| {
+			source.replace("color: vec3<f32>;", "color: vec3<f32>;\n\t[[location(2)]] a: f32;")
+		});
+		assert_eq!(result, Err("the vertex input 2 isn't one the pipeline has".to_string()));
+	}
+
+	#[test]
+	fn varyings_are_free_to_change() {
+		let result = check(|source| {
+			source.replace("location(0)]] color: vec4", "location(3)]] color: vec4")
+		});
+		assert_eq!(result, Ok(()));
+	}
+
+	#[test]
+	fn unmatched_fragment_input() {
+		let result = check(|source| {
+			let split = source.find("[[stage(fragment)]]").unwrap();
+			let fragment =
+				source[split..].replace("input: VertexOutput", "[[location(5)]] a: f32");
+			source[..split].to_string() + &fragment.replace("input.color", "vec4<f32>(a)")
+		});
+		assert_eq!(
+			result,
+			Err("the fragment input 5 isn't a matching vertex output".to_string())
+		);
+	}
+}
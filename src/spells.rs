@@ -1,4 +1,4 @@
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Element {
 	Earth,
 	Water,
@@ -12,25 +12,83 @@ pub enum Element {
 	Void,
 }
 
+#[derive(Clone)]
 pub struct Spell {
+	pub name: Option<String>, // set by recipes
 	pub element: Element,
 	pub is_inverted: bool,
 	// cast mods
-	pub range: f32, // todo
+	pub range: f32,
 	pub speed: f32,
-	pub cost: f32, // todo
+	pub cost: f32,
 	// effect mods
-	pub strength: f32, // todo
-	pub duration: f32, // todo
-	pub area: f32,     // todo
+	pub strength: f32,
+	pub duration: f32,
+	pub area: f32,
+	// flight
+	pub gravity: f32, // multiplier
+	pub light: f32,   // radius of the carried light, 0 for none
+	pub behaviours: Vec<Behaviour>,
 	// non-constant data
 	pub dist_traveled: f32,
+	pub age: f32,
+	pub pierced: Vec<usize>, // ids of what it has passed through or stopped in
+}
+
+// how a spell moves and what it does when it runs into something
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Behaviour {
+	Homing(f32), // turns towards the nearest target, in radians per second
+	Bounce(u32), // bounces left off the ground
+	Pierce(u32), // entities it can still pass through
+	Split(u32),  // shards it breaks into when it's done
+	Stationary,  // stays where it was cast until its duration runs out
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpellError {
+	Empty,
+	TooManyModifiers { count: usize, max: usize },
+	Conflicting(Element, Element), // modifiers that undo each other
+}
+
+// modifier pairs where one throws away what the other does
+const CONFLICTS: [(Element, Element); 2] =
+	[(Element::Earth, Element::Fire), (Element::Water, Element::Air)];
+
+impl std::fmt::Display for SpellError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			SpellError::Empty => write!(f, "no elements queued"),
+			SpellError::TooManyModifiers { count, max } => {
+				write!(f, "too many modifiers ({} of at most {})", count, max)
+			}
+			SpellError::Conflicting(a, b) => {
+				write!(f, "{} and {} cancel each other out", a.name(), b.name())
+			}
+		}
+	}
 }
 
 impl Spell {
-	pub fn new(elements: &[Element]) -> Spell {
+	// the first element is the spell's element and the rest modify it
+	pub fn new(elements: &[Element], max_modifiers: usize) -> Result<Spell, SpellError> {
+		let (element, modifiers) = elements.split_first().ok_or(SpellError::Empty)?;
+		if modifiers.len() > max_modifiers {
+			return Err(SpellError::TooManyModifiers {
+				count: modifiers.len(),
+				max: max_modifiers,
+			});
+		}
+		for (a, b) in CONFLICTS {
+			if modifiers.contains(&a) && modifiers.contains(&b) {
+				return Err(SpellError::Conflicting(a, b));
+			}
+		}
+
 		let mut spell = Spell {
-			element: elements[0],
+			name: None,
+			element: *element,
 			is_inverted: false,
 			range: 1.0,
 			speed: 1.0,
@@ -38,24 +96,38 @@ impl Spell {
 			strength: 1.0,
 			duration: 1.0,
 			area: 1.0,
+			gravity: 1.0,
+			light: 0.0,
+			behaviours: vec![],
 			dist_traveled: 0.0,
+			age: 0.0,
+			pierced: vec![],
 		};
-		for modifier in &elements[1..] {
+		let (mut homing, mut bounces, mut pierces, mut shards) = (0.0, 0, 0, 0);
+		for modifier in modifiers {
 			match modifier {
 				Element::Earth => spell.speed = 0.0,
 				Element::Water => spell.range = 0.0,
-				Element::Air => spell.range += 1.0,
+				Element::Air => {
+					spell.range += 1.0;
+					bounces += 1;
+				}
 				Element::Fire => spell.speed += 1.0,
 				Element::Acid => spell.duration += 1.0,
 				Element::Pressure => {
 					spell.area *= 0.5;
 					spell.strength += 0.5;
+					pierces += 1;
 				}
 				Element::Shock => {
 					spell.duration *= 0.5;
 					spell.strength += 0.5;
+					homing += 3.0;
+				}
+				Element::Radiance => {
+					spell.area += 1.0;
+					shards += 3;
 				}
-				Element::Radiance => spell.area += 1.0,
 				Element::Life => spell.strength += 1.0,
 				Element::Void => spell.is_inverted = !spell.is_inverted,
 			}
@@ -65,6 +137,169 @@ impl Spell {
 				_ => 1.0,
 			}
 		}
-		spell
+		if homing > 0.0 {
+			spell.behaviours.push(Behaviour::Homing(homing));
+		}
+		if bounces > 0 {
+			spell.behaviours.push(Behaviour::Bounce(bounces));
+		}
+		if pierces > 0 {
+			spell.behaviours.push(Behaviour::Pierce(pierces));
+		}
+		if shards > 0 {
+			spell.behaviours.push(Behaviour::Split(shards));
+		}
+		spell.light = match spell.element {
+			Element::Radiance => 0.5 * spell.area,
+			Element::Fire => 0.4,
+			_ => 0.0,
+		};
+		spell.settle();
+		Ok(spell)
+	}
+
+	// a spell that ends up with no speed, however it got there, stays where it's cast
+	pub fn settle(&mut self) {
+		if self.speed == 0.0 && !self.is_stationary() {
+			self.behaviours.push(Behaviour::Stationary);
+		}
+	}
+
+	pub fn is_stationary(&self) -> bool {
+		self.behaviours.contains(&Behaviour::Stationary)
+	}
+
+	pub fn homing(&self) -> Option<f32> {
+		self.behaviours.iter().find_map(|behaviour| match behaviour {
+			Behaviour::Homing(rate) => Some(*rate),
+			_ => None,
+		})
+	}
+
+	// uses up one bounce, returning false if there were none left
+	pub fn bounce(&mut self) -> bool {
+		self.behaviours.iter_mut().any(|behaviour| match behaviour {
+			Behaviour::Bounce(n) if *n > 0 => {
+				*n -= 1;
+				true
+			}
+			_ => false,
+		})
+	}
+
+	// uses up one pierce, returning false if there were none left
+	pub fn pierce(&mut self) -> bool {
+		self.behaviours.iter_mut().any(|behaviour| match behaviour {
+			Behaviour::Pierce(n) if *n > 0 => {
+				*n -= 1;
+				true
+			}
+			_ => false,
+		})
+	}
+
+	pub fn shards(&self) -> u32 {
+		self.behaviours
+			.iter()
+			.map(|behaviour| match behaviour {
+				Behaviour::Split(n) => *n,
+				_ => 0,
+			})
+			.sum()
+	}
+}
+
+impl Behaviour {
+	// as much of the behaviour as one modifier gives, for recipes to add by name
+	pub fn from_name(name: &str) -> Option<Behaviour> {
+		Some(match name {
+			"homing" => Behaviour::Homing(3.0),
+			"bounce" => Behaviour::Bounce(1),
+			"pierce" => Behaviour::Pierce(1),
+			"split" => Behaviour::Split(3),
+			"stationary" => Behaviour::Stationary,
+			_ => return None,
+		})
+	}
+}
+
+impl std::fmt::Display for Behaviour {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Behaviour::Homing(_) => write!(f, "homing"),
+			Behaviour::Bounce(n) => write!(f, "bounces {}", n),
+			Behaviour::Pierce(n) => write!(f, "pierces {}", n),
+			Behaviour::Split(n) => write!(f, "splits {}", n),
+			Behaviour::Stationary => write!(f, "stationary"),
+		}
+	}
+}
+
+impl Element {
+	pub const ALL: [Element; 10] = [
+		Element::Earth,
+		Element::Water,
+		Element::Air,
+		Element::Fire,
+		Element::Acid,
+		Element::Pressure,
+		Element::Shock,
+		Element::Radiance,
+		Element::Life,
+		Element::Void,
+	];
+
+	pub fn from_name(name: &str) -> Option<Element> {
+		Element::ALL.iter().find(|element| element.name() == name).copied()
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			Element::Earth => "earth",
+			Element::Water => "water",
+			Element::Air => "air",
+			Element::Fire => "fire",
+			Element::Acid => "acid",
+			Element::Pressure => "pressure",
+			Element::Shock => "shock",
+			Element::Radiance => "radiance",
+			Element::Life => "life",
+			Element::Void => "void",
+		}
+	}
+
+	pub fn color(self) -> crate::render::Color {
+		match self {
+			Element::Earth => [0.4, 0.2, 0.0],
+			Element::Void => [0.0, 0.0, 0.0],
+			Element::Life => [1.0, 1.0, 1.0],
+			Element::Water => [0.0, 0.0, 1.0],
+			Element::Air => [0.0, 1.0, 1.0],
+			Element::Fire => [1.0, 0.0, 0.0],
+			Element::Acid => [0.0, 1.0, 0.0],
+			Element::Shock => [1.0, 1.0, 0.0],
+			Element::Pressure => [0.4, 0.6, 1.0],
+			Element::Radiance => [1.0, 0.0, 1.0],
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn new_rejects_spells_that_cant_be_cast() {
+		assert_eq!(Spell::new(&[], 4).err(), Some(SpellError::Empty));
+		let elements = [Element::Fire, Element::Air, Element::Air, Element::Air];
+		let error = Spell::new(&elements, 2).err();
+		assert_eq!(error, Some(SpellError::TooManyModifiers { count: 3, max: 2 }));
+		assert!(Spell::new(&elements, 3).is_ok());
+		for (a, b) in CONFLICTS {
+			let error = Spell::new(&[Element::Life, b, Element::Void, a], 4).err();
+			assert_eq!(error, Some(SpellError::Conflicting(a, b)));
+			// the spell's own element doesn't count against its modifiers
+			assert!(Spell::new(&[a, b], 4).is_ok());
+		}
 	}
 }
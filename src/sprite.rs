@@ -0,0 +1,131 @@
+use crate::vector::Vector;
+use std::collections::HashMap;
+
+pub struct Atlas {
+	pub width: u32,
+	pub height: u32,
+	pub pixels: Vec<u8>, // rgba8, row-major from the top left
+	pub animations: HashMap<String, Animation>,
+}
+
+// a horizontal strip of equally sized frames in the atlas, in pixels
+#[derive(Clone, Copy, Debug)]
+pub struct Animation {
+	pub x: u32,
+	pub y: u32,
+	pub width: u32,
+	pub height: u32,
+	pub frames: u32,
+	pub fps: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Sprite {
+	pub animation: String,
+	pub time: f32,
+	pub flipped: bool, // mirrored horizontally
+}
+
+impl Atlas {
+	// the layout file has one animation per line: `name x y width height frames fps`
+	pub fn load(image_path: &str, layout_path: &str) -> Result<Atlas, String> {
+		let file =
+			std::fs::File::open(image_path).map_err(|e| format!("{}: {}", image_path, e))?;
+		let mut decoder = png::Decoder::new(file);
+		decoder.set_transformations(png::Transformations::EXPAND);
+		let mut reader = decoder.read_info().map_err(|e| format!("{}: {}", image_path, e))?;
+		let mut buffer = vec![0; reader.output_buffer_size()];
+		let info =
+			reader.next_frame(&mut buffer).map_err(|e| format!("{}: {}", image_path, e))?;
+		let pixels = match info.color_type {
+			png::ColorType::Rgba => buffer[..info.buffer_size()].to_vec(),
+			png::ColorType::Rgb => buffer[..info.buffer_size()]
+				.chunks(3)
+				.flat_map(|c| [c[0], c[1], c[2], 255])
+				.collect(),
+			png::ColorType::GrayscaleAlpha => buffer[..info.buffer_size()]
+				.chunks(2)
+				.flat_map(|c| [c[0], c[0], c[0], c[1]])
+				.collect(),
+			png::ColorType::Grayscale => {
+				buffer[..info.buffer_size()].iter().flat_map(|&c| [c, c, c, 255]).collect()
+			}
+			png::ColorType::Indexed => unreachable!(), // expanded by the decoder
+		};
+
+		let layout = std::fs::read_to_string(layout_path)
+			.map_err(|e| format!("{}: {}", layout_path, e))?;
+		let mut animations = HashMap::new();
+		for (i, line) in layout.lines().enumerate() {
+			let words: Vec<&str> = line.split_whitespace().collect();
+			if words.is_empty() || words[0].starts_with('#') {
+				continue;
+			}
+			let error = || {
+				format!("{}:{}: expected `name x y width height frames fps`", layout_path, i + 1)
+			};
+			if words.len() != 7 {
+				return Err(error());
+			}
+			let number = |s: &str| s.parse::<u32>().map_err(|_| error());
+			let animation = Animation {
+				x: number(words[1])?,
+				y: number(words[2])?,
+				width: number(words[3])?,
+				height: number(words[4])?,
+				frames: number(words[5])?.max(1),
+				fps: words[6].parse().map_err(|_| error())?,
+			};
+			if animation.x + animation.width * animation.frames > info.width
+				|| animation.y + animation.height > info.height
+			{
+				return Err(format!(
+					"{}:{}: animation is outside of the image",
+					layout_path,
+					i + 1
+				));
+			}
+			animations.insert(words[0].to_string(), animation);
+		}
+
+		Ok(Atlas { width: info.width, height: info.height, pixels, animations })
+	}
+
+	// returns the top left and bottom right texture coordinates of the current frame
+	pub fn uv(&self, sprite: &Sprite) -> Option<(Vector, Vector)> {
+		let animation = self.animations.get(&sprite.animation)?;
+		let frame = (sprite.time * animation.fps) as u32 % animation.frames;
+		let (min, max) = self.frame_uv(&sprite.animation, frame)?;
+		if sprite.flipped {
+			Some((Vector::new(max.x, min.y), Vector::new(min.x, max.y)))
+		} else {
+			Some((min, max))
+		}
+	}
+
+	pub fn frame_uv(&self, animation: &str, frame: u32) -> Option<(Vector, Vector)> {
+		let animation = self.animations.get(animation)?;
+		let x = animation.x + frame.min(animation.frames - 1) * animation.width;
+		let min =
+			Vector::new(x as f32 / self.width as f32, animation.y as f32 / self.height as f32);
+		let size = Vector::new(
+			animation.width as f32 / self.width as f32,
+			animation.height as f32 / self.height as f32,
+		);
+		Some((min, min + size))
+	}
+}
+
+impl Sprite {
+	pub fn new(animation: &str) -> Sprite {
+		Sprite { animation: animation.to_string(), time: 0.0, flipped: false }
+	}
+
+	// switches animations, restarting the clock only if the animation changed
+	pub fn play(&mut self, animation: &str) {
+		if self.animation != animation {
+			self.animation = animation.to_string();
+			self.time = 0.0;
+		}
+	}
+}
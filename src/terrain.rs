@@ -0,0 +1,78 @@
+use crate::object::aabb_aabb;
+use crate::vector::Vector;
+use std::collections::HashMap;
+
+const CELL: f32 = 0.25; // width and height of a grid cell
+
+// a uniform grid of the ids of terrain blocks overlapping each cell,
+// so collision only has to look at blocks near whatever is moving
+#[derive(Default)]
+pub struct Grid {
+	cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl Grid {
+	fn cells(min: Vector, max: Vector) -> impl Iterator<Item = (i32, i32)> {
+		let (x0, y0) = ((min.x / CELL).floor() as i32, (min.y / CELL).floor() as i32);
+		let (x1, y1) = ((max.x / CELL).floor() as i32, (max.y / CELL).floor() as i32);
+		(x0..=x1).flat_map(move |x| (y0..=y1).map(move |y| (x, y)))
+	}
+
+	pub fn insert(&mut self, id: usize, min: Vector, max: Vector) {
+		for cell in Grid::cells(min, max) {
+			self.cells.entry(cell).or_default().push(id);
+		}
+	}
+
+	// min and max have to be the same as when the block was inserted
+	pub fn remove(&mut self, id: usize, min: Vector, max: Vector) {
+		for cell in Grid::cells(min, max) {
+			if let Some(ids) = self.cells.get_mut(&cell) {
+				ids.retain(|i| *i != id);
+				if ids.is_empty() {
+					self.cells.remove(&cell);
+				}
+			}
+		}
+	}
+
+	// every block in a cell that the box touches, each once and in order
+	pub fn query(&self, min: Vector, max: Vector) -> Vec<usize> {
+		let mut ids: Vec<usize> = Grid::cells(min, max)
+			.filter_map(|cell| self.cells.get(&cell))
+			.flatten()
+			.copied()
+			.collect();
+		ids.sort_unstable();
+		ids.dedup();
+		ids
+	}
+}
+
+// the boxes left of (min, max) after cutting the hole out of it: up to a strip
+// below, above, left and right of the hole, or the whole box if they don't overlap
+pub fn subtract(
+	min: Vector,
+	max: Vector,
+	hole_min: Vector,
+	hole_max: Vector,
+) -> Vec<(Vector, Vector)> {
+	if !aabb_aabb(min, max, hole_min, hole_max) {
+		return vec![(min, max)];
+	}
+	let mut pieces = vec![];
+	if hole_min.y > min.y {
+		pieces.push((min, Vector::new(max.x, hole_min.y)));
+	}
+	if hole_max.y < max.y {
+		pieces.push((Vector::new(min.x, hole_max.y), max));
+	}
+	let (bottom, top) = (hole_min.y.max(min.y), hole_max.y.min(max.y));
+	if hole_min.x > min.x {
+		pieces.push((Vector::new(min.x, bottom), Vector::new(hole_min.x, top)));
+	}
+	if hole_max.x < max.x {
+		pieces.push((Vector::new(hole_max.x, bottom), Vector::new(max.x, top)));
+	}
+	pieces
+}
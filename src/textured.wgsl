@@ -0,0 +1,30 @@
+struct VertexInput {
+	[[location(0)]] pos: vec2<f32>;
+	[[location(1)]] uv: vec2<f32>;
+	[[location(2)]] color: vec3<f32>;
+};
+
+struct VertexOutput {
+	[[builtin(position)]] pos: vec4<f32>;
+	[[location(0)]] uv: vec2<f32>;
+	[[location(1)]] color: vec4<f32>;
+};
+
+[[group(0), binding(0)]]
+var texture: texture_2d<f32>;
+[[group(0), binding(1)]]
+var texture_sampler: sampler;
+
+[[stage(vertex)]]
+fn vertex(input: VertexInput) -> VertexOutput {
+	var output: VertexOutput;
+	output.pos = vec4<f32>(input.pos, 0.0, 1.0);
+	output.uv = input.uv;
+	output.color = vec4<f32>(input.color, 1.0);
+	return output;
+}
+
+[[stage(fragment)]]
+fn fragment(input: VertexOutput) -> [[location(0)]] vec4<f32> {
+	return textureSample(texture, texture_sampler, input.uv) * input.color;
+}
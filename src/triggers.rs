@@ -0,0 +1,64 @@
+use crate::render::Color;
+use crate::spells::Element;
+use crate::Id;
+use std::fmt;
+
+// what happens when something goes into a trigger volume, besides its script's `enter` event
+#[derive(Clone, Debug)]
+pub enum Kind {
+	Exit(String),    // the player moves on to the level at this path
+	Checkpoint,      // the player comes back here when they run out of health
+	Damage(f32),     // health lost per second while the player is inside
+	Switch(Element), // only spells of this element set it off, for its script to act on
+}
+
+// a volume that notices the player and spells passing through without stopping them
+pub struct Trigger {
+	pub kind: Kind,
+	pub inside: Vec<Id>, // what was in it as of the last tick
+}
+
+impl Kind {
+	pub fn parse(words: &[&str]) -> Result<Kind, String> {
+		match words {
+			["exit", path] => Ok(Kind::Exit(path.to_string())),
+			["checkpoint"] => Ok(Kind::Checkpoint),
+			["damage", amount] => {
+				amount.parse().map(Kind::Damage).map_err(|_| format!("bad number `{}`", amount))
+			}
+			["switch", element] => Element::from_name(element)
+				.map(Kind::Switch)
+				.ok_or_else(|| format!("unknown element `{}`", element)),
+			_ => Err("expected `exit path`, `checkpoint`, `damage amount` or `switch element`"
+				.to_string()),
+		}
+	}
+
+	// what sets the trigger off: the player, or spells of the element a switch wants
+	pub fn notices(&self, spell: Option<Element>) -> bool {
+		match self {
+			Kind::Switch(element) => spell == Some(*element),
+			_ => spell.is_none(),
+		}
+	}
+
+	pub fn color(&self) -> Color {
+		match self {
+			Kind::Exit(_) => [0.4, 1.0, 0.4],
+			Kind::Checkpoint => [0.4, 0.7, 1.0],
+			Kind::Damage(_) => [1.0, 0.3, 0.3],
+			Kind::Switch(element) => element.color(),
+		}
+	}
+}
+
+impl fmt::Display for Kind {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Kind::Exit(path) => write!(f, "exit {}", path),
+			Kind::Checkpoint => write!(f, "checkpoint"),
+			Kind::Damage(amount) => write!(f, "damage {}", amount),
+			Kind::Switch(element) => write!(f, "switch {}", element.name()),
+		}
+	}
+}
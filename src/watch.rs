@@ -0,0 +1,46 @@
+use std::time::{Duration, Instant, SystemTime};
+
+const INTERVAL: Duration = Duration::from_millis(500);
+
+// notices when files change by polling their modification times
+pub struct Watcher {
+	files: Vec<(String, Option<SystemTime>)>,
+	last_poll: Instant,
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+	std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+impl Watcher {
+	pub fn new(paths: &[String]) -> Watcher {
+		Watcher {
+			files: paths.iter().map(|path| (path.clone(), modified(path))).collect(),
+			last_poll: Instant::now(),
+		}
+	}
+
+	// starts watching another file, if it isn't already
+	pub fn watch(&mut self, path: &str) {
+		if !self.files.iter().any(|(file, _)| file == path) {
+			self.files.push((path.to_string(), modified(path)));
+		}
+	}
+
+	// the files that changed since they were last looked at, which is at most every interval
+	pub fn poll(&mut self) -> Vec<String> {
+		if self.last_poll.elapsed() < INTERVAL {
+			return vec![];
+		}
+		self.last_poll = Instant::now();
+		let mut changed = vec![];
+		for (path, time) in &mut self.files {
+			let now = modified(path);
+			if now != *time {
+				*time = now;
+				changed.push(path.clone());
+			}
+		}
+		changed
+	}
+}